@@ -0,0 +1,94 @@
+//! Throughput of the stripping library on the files in `testdata/`, which
+//! `mise run testdata` generates.
+//!
+//! Input is read in chunks of the buffer size, as from a file or pipe, since a
+//! single slice would let the default options skip straight to its end.
+
+use std::fs;
+use std::hint::black_box;
+use std::io;
+use std::path::Path;
+
+use criterion::BenchmarkId;
+use criterion::Criterion;
+use criterion::Throughput;
+use criterion::criterion_group;
+use criterion::criterion_main;
+use nln::Options;
+
+// shared with `cargo xtask gen-testdata`, which uses more of it
+#[path = "../xtask/src/testdata.rs"]
+#[allow(dead_code)]
+mod testdata;
+
+use testdata::FileVariant;
+use testdata::Size;
+
+const SIZES: [Size; 3] = [Size::Small, Size::Large, Size::Huge];
+
+/// Reads `testdata/{name}`, or returns `None` if it hasn't been generated.
+fn load(name: &str) -> Option<Vec<u8>> {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("testdata")
+        .join(name);
+    match fs::read(&path) {
+        Ok(content) => Some(content),
+        Err(_) => {
+            eprintln!("skipping {name}: run `mise run testdata` to generate it");
+            None
+        }
+    }
+}
+
+fn variants(c: &mut Criterion) {
+    let configurations = [
+        ("default", Options::default()),
+        ("keep", Options::builder().keep_trailing(1).build()),
+        ("squeeze", Options::builder().squeeze(true).build()),
+    ];
+    for size in SIZES {
+        let mut group = c.benchmark_group(size.as_str());
+        if matches!(size, Size::Huge) {
+            group.sample_size(10);
+        }
+        for variant in FileVariant::ALL {
+            let Some(input) = load(&testdata::file_name(size, variant)) else {
+                continue;
+            };
+            group.throughput(Throughput::Bytes(input.len() as u64));
+            for (name, options) in &configurations {
+                group.bench_with_input(
+                    BenchmarkId::new(*name, variant.as_str()),
+                    &input,
+                    |b, input| {
+                        b.iter(|| {
+                            nln::process_read(black_box(input.as_slice()), &mut io::sink(), options)
+                        });
+                    },
+                );
+            }
+        }
+        group.finish();
+    }
+}
+
+fn buffer_sizes(c: &mut Criterion) {
+    let Some(input) = load(&testdata::file_name(
+        Size::Large,
+        FileVariant::ManyTrailingThenContent,
+    )) else {
+        return;
+    };
+    let mut group = c.benchmark_group("buffer_size");
+    group.throughput(Throughput::Bytes(input.len() as u64));
+    for size in [8 * 1024, 64 * 1024, 256 * 1024, 1024 * 1024] {
+        let options = Options::builder().buffer_size(size).build();
+        group.bench_with_input(BenchmarkId::from_parameter(size), &input, |b, input| {
+            b.iter(|| nln::process_read(black_box(input.as_slice()), &mut io::sink(), &options));
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, variants, buffer_sizes);
+criterion_main!(benches);
@@ -1,6 +1,7 @@
 #!/bin/bash
 # Benchmark nnl with various test data files
 # Usage: ./benchmark.sh BIN [BASELINE_BIN]
+# Set NLN_ARGS to benchmark with options, e.g. NLN_ARGS=--squeeze
 
 set -e
 
@@ -17,6 +18,7 @@ BINARY="$1"
 BASELINE_BINARY="$2"
 TESTDATA_DIR="testdata"
 BENCHMARKS_DIR="benchmarks"
+ARGS="${NLN_ARGS:-}"
 
 # Validate primary binary
 if [ ! -f "$BINARY" ]; then
@@ -54,6 +56,9 @@ echo ""
 echo "Running benchmarks..."
 echo "====================="
 echo "Binary: $BINARY"
+if [ -n "$ARGS" ]; then
+  echo "Arguments: $ARGS"
+fi
 if [ -n "$BASELINE_BINARY" ]; then
   echo "Baseline: $BASELINE_BINARY"
 fi
@@ -69,14 +74,14 @@ for file in "$TESTDATA_DIR"/*.txt; do
     hyperfine \
       --warmup 5 \
       --export-markdown "$BENCHMARKS_DIR/${filename%.txt}.md" \
-      --command-name "current" "$BINARY < $file > /dev/null" \
-      --command-name "baseline" "$BASELINE_BINARY < $file > /dev/null"
+      --command-name "current" "$BINARY $ARGS < $file > /dev/null" \
+      --command-name "baseline" "$BASELINE_BINARY $ARGS < $file > /dev/null"
   else
     # Benchmark single binary
     hyperfine \
       --warmup 5 \
       --export-markdown "$BENCHMARKS_DIR/${filename%.txt}.md" \
-      "$BINARY < $file > /dev/null"
+      "$BINARY $ARGS < $file > /dev/null"
   fi
 
   echo ""
@@ -0,0 +1,174 @@
+//! Analyzing input without writing output.
+
+use std::io::BufRead;
+use std::io::ErrorKind;
+use std::io::Result;
+
+use crate::LineEnding;
+
+/// What [`check`] found out about an input.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct CheckResult {
+    /// `\n` terminators not preceded by `\r`.
+    pub lf: u64,
+    /// `\r\n` terminators.
+    pub crlf: u64,
+    /// `\r` terminators not followed by `\n`.
+    pub cr: u64,
+    /// Terminators in the run of newlines at the end of the input.
+    pub trailing: u64,
+    /// Bytes in the run of newlines at the end of the input, which is what
+    /// [`strip_trailing_newlines`](crate::strip_trailing_newlines) would
+    /// remove.
+    pub trailing_bytes: u64,
+}
+
+impl CheckResult {
+    /// Whether the input ends in newlines.
+    pub fn has_trailing_newlines(&self) -> bool {
+        self.trailing > 0
+    }
+
+    /// The more common of `\n` and `\r\n`, or `None` if there are neither.
+    /// Ties go to `\n`.
+    pub fn dominant_line_ending(&self) -> Option<LineEnding> {
+        match (self.lf, self.crlf) {
+            (0, 0) => None,
+            (lf, crlf) if crlf > lf => Some(LineEnding::CrLf),
+            _ => Some(LineEnding::Lf),
+        }
+    }
+}
+
+/// Reads the input to the end and reports its line endings and trailing
+/// newlines, without producing any output.
+///
+/// ```
+/// let result = nln_core::check(b"a\r\nb\n\n".as_slice())?;
+/// assert!(result.has_trailing_newlines());
+/// assert_eq!((result.lf, result.crlf, result.trailing_bytes), (2, 1, 2));
+/// # Ok::<(), std::io::Error>(())
+/// ```
+///
+/// ## Errors
+///
+/// This function will return an error if the reader cannot be read.
+pub fn check(mut input: impl BufRead) -> Result<CheckResult> {
+    let mut result = CheckResult::default();
+    let mut pending_cr = false;
+    loop {
+        let buf = match input.fill_buf() {
+            Ok(buf) => buf,
+            Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+            Err(err) => return Err(err),
+        };
+        if buf.is_empty() {
+            break;
+        }
+        for &b in buf {
+            match b {
+                b'\n' if pending_cr => {
+                    // the CR was already counted as a trailing terminator
+                    result.crlf += 1;
+                    result.trailing_bytes += 1;
+                    pending_cr = false;
+                }
+                b'\n' => {
+                    result.lf += 1;
+                    result.trailing += 1;
+                    result.trailing_bytes += 1;
+                }
+                b'\r' => {
+                    if pending_cr {
+                        result.cr += 1;
+                    }
+                    result.trailing += 1;
+                    result.trailing_bytes += 1;
+                    pending_cr = true;
+                }
+                _ => {
+                    if pending_cr {
+                        result.cr += 1;
+                    }
+                    result.trailing = 0;
+                    result.trailing_bytes = 0;
+                    pending_cr = false;
+                }
+            }
+        }
+        let n = buf.len();
+        input.consume(n);
+    }
+    if pending_cr {
+        result.cr += 1;
+    }
+    Ok(result)
+}
+
+/// Reads the input to the end and returns its dominant line ending, to
+/// normalize to with [`OptionsBuilder::line_ending`] so that a file keeps its
+/// convention. See [`CheckResult::dominant_line_ending`].
+///
+/// ```
+/// let eol = nln_core::detect_eol(b"a\r\nb\r\nc\n".as_slice())?;
+/// assert_eq!(eol, Some(nln_core::LineEnding::CrLf));
+/// # Ok::<(), std::io::Error>(())
+/// ```
+///
+/// [`OptionsBuilder::line_ending`]: crate::OptionsBuilder::line_ending
+///
+/// ## Errors
+///
+/// This function will return an error if the reader cannot be read.
+pub fn detect_eol(input: impl BufRead) -> Result<Option<LineEnding>> {
+    Ok(check(input)?.dominant_line_ending())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CheckResult;
+    use super::check;
+    use crate::LineEnding;
+
+    #[test]
+    fn test_empty() {
+        assert_eq!(check(b"".as_slice()).unwrap(), CheckResult::default());
+    }
+
+    #[test]
+    fn test_mixed() {
+        assert_eq!(
+            check(b"a\nb\r\nc\rd\r\n\n\r".as_slice()).unwrap(),
+            CheckResult {
+                lf: 2,
+                crlf: 2,
+                cr: 2,
+                trailing: 3,
+                trailing_bytes: 4,
+            }
+        );
+    }
+
+    #[test]
+    fn test_crlf_split_across_chunks() {
+        let input = std::io::BufReader::with_capacity(2, b"a\r\nb".as_slice());
+        let result = check(input).unwrap();
+        assert_eq!(result.crlf, 1);
+        assert_eq!(result.cr, 0);
+        assert!(!result.has_trailing_newlines());
+    }
+
+    #[test]
+    fn test_dominant_line_ending() {
+        for (input, expected) in [
+            ("", None),
+            ("a\r\r", None),
+            ("a\nb\r\n", Some(LineEnding::Lf)),
+            ("a\nb\r\nc\r\n\r", Some(LineEnding::CrLf)),
+        ] {
+            let result = check(input.as_bytes()).unwrap();
+            assert_eq!(result.dominant_line_ending(), expected, "{input:?}");
+        }
+    }
+}
@@ -0,0 +1,52 @@
+//! Gathering small pieces of output into fewer writes.
+
+use std::io::IoSlice;
+use std::io::Result;
+use std::io::Write;
+
+use crate::write_all_vectored;
+use crate::write_pieces;
+
+/// Copies output into a buffer until `threshold` bytes have gathered, as set
+/// with [`OptionsBuilder::coalesce_writes`](crate::OptionsBuilder::coalesce_writes).
+pub(crate) struct Coalescer {
+    buffer: Vec<u8>,
+    threshold: usize,
+}
+
+impl Coalescer {
+    pub(crate) fn new(threshold: usize) -> Self {
+        Self {
+            buffer: Vec::new(),
+            threshold,
+        }
+    }
+
+    /// Writes `pieces`, or keeps them for later while the buffer has room.
+    pub(crate) fn write<'a>(
+        &mut self,
+        o: &mut impl Write,
+        pieces: impl Iterator<Item = &'a [u8]>,
+    ) -> Result<()> {
+        if self.threshold == 0 {
+            return write_pieces(o, pieces);
+        }
+        for piece in pieces {
+            if self.buffer.len() + piece.len() <= self.threshold {
+                self.buffer.extend_from_slice(piece);
+            } else {
+                // a piece too big to gather goes out with what came before it
+                write_all_vectored(o, &mut [IoSlice::new(&self.buffer), IoSlice::new(piece)])?;
+                self.buffer.clear();
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes what has gathered so far.
+    pub(crate) fn finish(&mut self, o: &mut impl Write) -> Result<()> {
+        o.write_all(&self.buffer)?;
+        self.buffer.clear();
+        Ok(())
+    }
+}
@@ -0,0 +1,114 @@
+//! A [`tokio_util::codec`] integration over [`Stripper`].
+
+use std::io;
+
+use bytes::Bytes;
+use bytes::BytesMut;
+use tokio_util::codec::Decoder;
+
+use crate::BytesStripper;
+use crate::Options;
+use crate::Stats;
+
+/// A [`Decoder`] that strips trailing newlines, for use with
+/// [`FramedRead`](tokio_util::codec::FramedRead).
+///
+/// Each frame is whatever output the bytes read so far produce, so frames
+/// don't line up with lines. The trailing terminators are dropped when the
+/// underlying reader ends.
+///
+/// There is no matching [`Encoder`](tokio_util::codec::Encoder), since
+/// encoders aren't told when the stream ends. Wrap the writer in
+/// [`StripTrailingNewlines`](crate::StripTrailingNewlines) instead.
+#[derive(Debug, Clone)]
+pub struct StripTrailingNewlinesCodec {
+    stripper: BytesStripper,
+    finished: bool,
+}
+
+impl StripTrailingNewlinesCodec {
+    /// Creates a codec with the default options.
+    pub fn new() -> Self {
+        Self::with_options(Options::default())
+    }
+
+    /// Creates a codec that processes the stream as configured by `options`.
+    pub fn with_options(options: Options) -> Self {
+        Self {
+            stripper: BytesStripper::new(options),
+            finished: false,
+        }
+    }
+
+    /// Statistics about the stream so far.
+    pub fn stats(&self) -> Stats {
+        self.stripper.stats()
+    }
+}
+
+impl Default for StripTrailingNewlinesCodec {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Decoder for StripTrailingNewlinesCodec {
+    type Item = Bytes;
+    type Error = io::Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> io::Result<Option<Bytes>> {
+        if src.is_empty() {
+            return Ok(None);
+        }
+        let frame = src.split().freeze();
+        Ok(join(self.stripper.push(&frame)?))
+    }
+
+    fn decode_eof(&mut self, src: &mut BytesMut) -> io::Result<Option<Bytes>> {
+        let frame = src.split().freeze();
+        let mut out = BytesMut::new();
+        out.extend(self.stripper.push(&frame)?);
+        if !self.finished {
+            self.finished = true;
+            out.extend(self.stripper.finish());
+        }
+        Ok((!out.is_empty()).then(|| out.freeze()))
+    }
+}
+
+/// Joins `pieces` into one frame, without copying if there is only one.
+fn join(mut pieces: impl Iterator<Item = Bytes>) -> Option<Bytes> {
+    let first = pieces.next()?;
+    let Some(second) = pieces.next() else {
+        return Some(first);
+    };
+    let mut out = BytesMut::from(first);
+    out.extend([second]);
+    out.extend(pieces);
+    Some(out.freeze())
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::BytesMut;
+    use tokio_util::codec::Decoder;
+
+    use crate::Options;
+    use crate::StripTrailingNewlinesCodec;
+
+    #[test]
+    fn test_decode() {
+        let options = Options::builder().keep_trailing(1).build();
+        let mut codec = StripTrailingNewlinesCodec::with_options(options);
+        let mut src = BytesMut::from("a\n\n");
+        assert_eq!(codec.decode(&mut src).unwrap().unwrap(), "a");
+        assert!(src.is_empty());
+        assert_eq!(codec.decode(&mut src).unwrap(), None);
+
+        src.extend_from_slice(b"b\n\n\n");
+        assert_eq!(codec.decode(&mut src).unwrap().unwrap(), "\n\nb");
+        src.extend_from_slice(b"\n");
+        assert_eq!(codec.decode_eof(&mut src).unwrap().unwrap(), "\n");
+        assert_eq!(codec.decode_eof(&mut src).unwrap(), None);
+    }
+}
@@ -0,0 +1,63 @@
+//! The error type of [`process`](crate::process).
+
+use std::fmt;
+use std::io;
+
+/// An error while processing a stream, telling which side of it failed.
+///
+/// Converts into an [`io::Error`] of the same kind, or
+/// [`InvalidData`](io::ErrorKind::InvalidData) for [`Error::Limit`] and
+/// [`Other`](io::ErrorKind::Other) for [`Error::Cancelled`], so `?` keeps
+/// working in functions that return [`io::Result`].
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// The reader failed.
+    Read(io::Error),
+    /// The writer failed.
+    Write(io::Error),
+    /// More than this many bytes of newlines had to be held back, see
+    /// [`OptionsBuilder::max_pending_bytes`](crate::OptionsBuilder::max_pending_bytes).
+    Limit(u64),
+    /// Processing was stopped through
+    /// [`OptionsBuilder::cancel_flag`](crate::OptionsBuilder::cancel_flag).
+    Cancelled,
+}
+
+impl Error {
+    /// The underlying I/O error, if any.
+    pub fn io_error(&self) -> Option<&io::Error> {
+        match self {
+            Error::Read(err) | Error::Write(err) => Some(err),
+            Error::Limit(_) | Error::Cancelled => None,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Read(err) => write!(f, "failed to read input: {err}"),
+            Error::Write(err) => write!(f, "failed to write output: {err}"),
+            Error::Limit(limit) => write!(f, "more than {limit} bytes of newlines in a row"),
+            Error::Cancelled => write!(f, "cancelled"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.io_error().map(|err| err as _)
+    }
+}
+
+impl From<Error> for io::Error {
+    fn from(err: Error) -> Self {
+        let kind = match &err {
+            Error::Read(err) | Error::Write(err) => err.kind(),
+            Error::Limit(_) => io::ErrorKind::InvalidData,
+            Error::Cancelled => io::ErrorKind::Other,
+        };
+        io::Error::new(kind, err)
+    }
+}
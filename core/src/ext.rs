@@ -0,0 +1,120 @@
+//! Trimming owned buffers in place.
+
+use crate::Terminator;
+use crate::strip_trailing_newlines_bytes;
+
+/// Removes trailing newlines from an owned buffer without copying it.
+///
+/// Newlines are `\n` and `\r`, including a lone `\r`, exactly as in
+/// [`strip_trailing_newlines`](crate::strip_trailing_newlines).
+///
+/// ```
+/// use nln_core::StripTrailingNewlinesExt;
+///
+/// let mut s = String::from("hello\r\n\r");
+/// s.truncate_trailing_newlines();
+/// assert_eq!(s, "hello");
+/// ```
+pub trait StripTrailingNewlinesExt {
+    /// Truncates `self` to end before its trailing newlines.
+    fn truncate_trailing_newlines(&mut self);
+}
+
+impl StripTrailingNewlinesExt for Vec<u8> {
+    fn truncate_trailing_newlines(&mut self) {
+        let len = strip_trailing_newlines_bytes(self).len();
+        self.truncate(len);
+    }
+}
+
+impl StripTrailingNewlinesExt for String {
+    fn truncate_trailing_newlines(&mut self) {
+        // newlines are ASCII, so this is on a character boundary
+        let len = strip_trailing_newlines_bytes(self.as_bytes()).len();
+        self.truncate(len);
+    }
+}
+
+/// Removes exactly one trailing `\n`, `\r\n` or `\r` from `s`, like Perl's
+/// and Ruby's `chomp`, returning which one it was.
+///
+/// This undoes what [`BufRead::read_line`](std::io::BufRead::read_line)
+/// leaves behind, where
+/// [`truncate_trailing_newlines`](StripTrailingNewlinesExt::truncate_trailing_newlines)
+/// would also remove blank lines before it.
+///
+/// ```
+/// let mut line = String::from("hello\n\r\n");
+/// assert_eq!(nln_core::chomp(&mut line), Some(nln_core::Terminator::CrLf));
+/// assert_eq!(line, "hello\n");
+/// ```
+pub fn chomp(s: &mut String) -> Option<Terminator> {
+    let terminator = ascii_terminator_at_end(s.as_bytes())?;
+    // the terminator is ASCII, so this is on a character boundary
+    s.truncate(s.len() - terminator.as_bytes().len());
+    Some(terminator)
+}
+
+/// Like [`chomp`], for bytes.
+pub fn chomp_bytes(bytes: &mut Vec<u8>) -> Option<Terminator> {
+    let terminator = ascii_terminator_at_end(bytes)?;
+    bytes.truncate(bytes.len() - terminator.as_bytes().len());
+    Some(terminator)
+}
+
+fn ascii_terminator_at_end(bytes: &[u8]) -> Option<Terminator> {
+    Terminator::at_end(bytes).filter(|terminator| terminator.is_ascii())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::StripTrailingNewlinesExt;
+    use super::chomp;
+    use super::chomp_bytes;
+    use crate::Terminator;
+    use crate::strip_trailing_newlines;
+
+    #[test]
+    fn test_matches_streaming() {
+        for input in [
+            "",
+            "abc",
+            "\r",
+            "\n\r\n",
+            "a\rb\r",
+            "\n\nabc\r\n\n\r",
+            "é\n",
+        ] {
+            let mut expected = Vec::new();
+            strip_trailing_newlines(input.as_bytes(), &mut expected).unwrap();
+
+            let mut bytes = input.as_bytes().to_vec();
+            bytes.truncate_trailing_newlines();
+            assert_eq!(bytes, expected);
+
+            let mut s = input.to_string();
+            s.truncate_trailing_newlines();
+            assert_eq!(s.as_bytes(), expected);
+        }
+    }
+
+    #[test]
+    fn test_chomp() {
+        for (input, expected, terminator) in [
+            ("", "", None),
+            ("abc", "abc", None),
+            ("a\n\n", "a\n", Some(Terminator::Lf)),
+            ("a\r\r\n", "a\r", Some(Terminator::CrLf)),
+            ("a\n\r", "a\n", Some(Terminator::Cr)),
+            ("a\u{2028}", "a\u{2028}", None),
+        ] {
+            let mut s = input.to_string();
+            assert_eq!(chomp(&mut s), terminator, "{input:?}");
+            assert_eq!(s, expected);
+
+            let mut bytes = input.as_bytes().to_vec();
+            assert_eq!(chomp_bytes(&mut bytes), terminator);
+            assert_eq!(bytes, expected.as_bytes());
+        }
+    }
+}
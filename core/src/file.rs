@@ -0,0 +1,567 @@
+//! Processing files in place.
+
+use std::fs;
+use std::fs::File;
+use std::fs::OpenOptions;
+use std::fs::Permissions;
+use std::io;
+use std::io::BufWriter;
+use std::io::Read;
+use std::io::Seek;
+use std::io::SeekFrom;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+
+use crate::Error;
+use crate::Options;
+use crate::Stats;
+use crate::is_newline;
+use crate::process_read;
+
+/// How much of the end of a file [`check_file`] reads at a time.
+const TAIL_BLOCK: usize = 4 * 1024;
+
+/// Where the trailing newlines of a file are, as found by [`check_file`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct FileTail {
+    /// The length of the file.
+    pub len: u64,
+    /// The offset of the first trailing `\n` or `\r`, or [`len`](Self::len)
+    /// if there are none.
+    pub trailing_start: u64,
+}
+
+impl FileTail {
+    /// Whether the file ends in newlines.
+    pub fn has_trailing_newlines(&self) -> bool {
+        self.trailing_start < self.len
+    }
+
+    /// The number of bytes in the trailing newlines.
+    pub fn trailing_bytes(&self) -> u64 {
+        self.len - self.trailing_start
+    }
+}
+
+/// Finds the trailing newlines of the file at `path` by reading it backwards
+/// from the end, a few KiB at a time, so only the last line or so is read.
+///
+/// Newlines are `\n` and `\r`, as with
+/// [`strip_trailing_newlines`](crate::strip_trailing_newlines). Truncating
+/// the file to [`FileTail::trailing_start`] strips them.
+///
+/// ## Errors
+///
+/// This function will return an error if the file cannot be read.
+pub fn check_file(path: impl AsRef<Path>) -> io::Result<FileTail> {
+    check_seekable(File::open(path)?)
+}
+
+/// Like [`check_file`], for the rest of an open file or anything else
+/// seekable, from its current position on.
+///
+/// Offsets are positions in the whole stream, and the position is restored
+/// afterwards.
+///
+/// ## Errors
+///
+/// This function will return an error if `reader` cannot be read or seeked.
+pub fn check_seekable(mut reader: impl Read + Seek) -> io::Result<FileTail> {
+    let position = reader.stream_position()?;
+    let len = reader.seek(SeekFrom::End(0))?.max(position);
+    let mut block = vec![0; TAIL_BLOCK];
+    let mut end = len;
+    let mut trailing_start = position;
+    while end > position {
+        let start = end.saturating_sub(TAIL_BLOCK as u64).max(position);
+        let block = &mut block[..(end - start) as usize];
+        reader.seek(SeekFrom::Start(start))?;
+        reader.read_exact(block)?;
+        if let Some(i) = block.iter().rposition(|&b| !is_newline(b)) {
+            trailing_start = start + i as u64 + 1;
+            break;
+        }
+        end = start;
+    }
+    reader.seek(SeekFrom::Start(position))?;
+    Ok(FileTail {
+        len,
+        trailing_start,
+    })
+}
+
+/// Processes the file at `path` as configured by `options`, replacing its
+/// contents with the output.
+///
+/// When the output can only be a prefix of the input, as when only trailing
+/// newlines are stripped, the file is truncated in place, and only its
+/// trailing newlines are read. Otherwise the output goes to an
+/// [`AtomicFile`], so the file is never left half-written. Its permissions
+/// are kept either way.
+///
+/// Unlike the `nln` binary, this doesn't install a signal handler, so a
+/// temporary file can be left behind if the process is killed while writing
+/// it. Such files are named `.nln-tmp-*`.
+///
+/// ## Errors
+///
+/// This function will return [`Error::Read`] if the file cannot be read and
+/// [`Error::Write`] if it cannot be replaced. The file is unchanged then.
+pub fn process_file_in_place(path: impl AsRef<Path>, options: &Options) -> Result<Stats, Error> {
+    let path = path.as_ref();
+    if options.is_prefix_only() {
+        // read-only files can still be replaced by renaming
+        if let Ok(file) = OpenOptions::new().read(true).write(true).open(path) {
+            return truncate(&file, options);
+        }
+    }
+    rewrite(path, options)
+}
+
+/// Truncates `file` to what `options` keep of it. They only strip trailing
+/// newlines, so only those are read.
+fn truncate(mut file: &File, options: &Options) -> Result<Stats, Error> {
+    let tail = check_seekable(file).map_err(Error::Read)?;
+    let keep = if options.strip_trailing {
+        options.keep_trailing
+    } else {
+        usize::MAX
+    };
+    let mut stats = Stats {
+        bytes_in: tail.len,
+        bytes_out: tail.trailing_start,
+        chunks: 1,
+        ..Stats::default()
+    };
+    file.seek(SeekFrom::Start(tail.trailing_start))
+        .map_err(Error::Read)?;
+    let mut block = vec![0; TAIL_BLOCK];
+    let mut left = tail.trailing_bytes();
+    let mut terminators = 0;
+    let mut after_cr = false;
+    while left > 0 {
+        let block = &mut block[..left.min(TAIL_BLOCK as u64) as usize];
+        file.read_exact(block).map_err(Error::Read)?;
+        left -= block.len() as u64;
+        for &b in block.iter() {
+            // an LF after a CR ends the same terminator
+            if !(after_cr && b == b'\n') {
+                terminators += 1;
+            }
+            after_cr = b == b'\r';
+            if terminators <= keep {
+                stats.bytes_out += 1;
+            } else if b == b'\n' {
+                stats.lf_stripped += 1;
+            } else {
+                stats.cr_stripped += 1;
+            }
+        }
+    }
+    if let Some(progress) = &options.progress {
+        (progress.callback)(stats.bytes_in, stats.bytes_out);
+    }
+    if stats.bytes_out < stats.bytes_in {
+        file.set_len(stats.bytes_out).map_err(Error::Write)?;
+        file.sync_all().map_err(Error::Write)?;
+    }
+    Ok(stats)
+}
+
+fn rewrite(path: &Path, options: &Options) -> Result<Stats, Error> {
+    let input = File::open(path).map_err(Error::Read)?;
+    let permissions = input.metadata().map_err(Error::Read)?.permissions();
+    let mut temp = AtomicFile::create(path).map_err(Error::Write)?;
+    let mut output = BufWriter::with_capacity(options.buffer_size(), &mut temp);
+    let stats = process_read(input, &mut output, options)?;
+    output
+        .into_inner()
+        .map_err(io::IntoInnerError::into_error)
+        .map_err(Error::Write)?;
+    temp.set_permissions(permissions).map_err(Error::Write)?;
+    temp.commit().map_err(Error::Write)?;
+    Ok(stats)
+}
+
+/// A file written next to its target and renamed over it by
+/// [`commit`](Self::commit), so the target is left untouched until all of it
+/// has been written.
+///
+/// A symlink is followed, so the file it points to is replaced rather than
+/// the link. A target that exists but isn't a regular file, such as a FIFO or
+/// a device, is opened and written to directly instead, without a temporary
+/// file. On Unix, a target with other hard links, or whose owner the
+/// temporary file can't be given, is overwritten with the content instead,
+/// which keeps its identity but isn't atomic.
+///
+/// Dropping it without committing removes the temporary file. Nothing
+/// removes it if the process is killed, so callers that handle signals can
+/// do so with [`temp_path`](Self::temp_path). Such files are named
+/// `.nln-tmp-*`.
+#[derive(Debug)]
+pub struct AtomicFile {
+    file: File,
+    /// The temporary file, or `None` if the target is written directly.
+    temp: Option<PathBuf>,
+    target: PathBuf,
+}
+
+impl AtomicFile {
+    /// Creates the temporary file for `target`, next to it or to the file it
+    /// links to, or opens `target` if it isn't a regular file.
+    ///
+    /// ## Errors
+    ///
+    /// This function will return an error if the temporary file cannot be
+    /// created or the target cannot be opened.
+    pub fn create(target: impl AsRef<Path>) -> io::Result<Self> {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+        // a target that doesn't exist yet is created as named
+        let target = fs::canonicalize(&target).unwrap_or_else(|_| target.as_ref().to_path_buf());
+        // renaming over a FIFO or a device would replace it with a file
+        if fs::metadata(&target).is_ok_and(|metadata| !metadata.is_file()) {
+            let file = OpenOptions::new().write(true).open(&target)?;
+            return Ok(Self {
+                file,
+                temp: None,
+                target,
+            });
+        }
+        let dir = match target.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir,
+            _ => Path::new("."),
+        };
+        let temp = dir.join(format!(
+            ".nln-tmp-{}-{}",
+            process_tag(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        let file = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&temp)?;
+        Ok(Self {
+            file,
+            temp: Some(temp),
+            target,
+        })
+    }
+
+    /// The temporary file, or the target if it isn't a regular file, to write
+    /// to it directly.
+    pub fn file(&self) -> &File {
+        &self.file
+    }
+
+    /// Where the temporary file is, or `None` if the target is written
+    /// directly.
+    pub fn temp_path(&self) -> Option<&Path> {
+        self.temp.as_deref()
+    }
+
+    /// Sets the permissions the target will have. A target written directly
+    /// keeps its own.
+    ///
+    /// ## Errors
+    ///
+    /// This function will return an error if the permissions cannot be set.
+    pub fn set_permissions(&self, permissions: Permissions) -> io::Result<()> {
+        match self.temp {
+            Some(_) => set_permissions(&self.file, permissions),
+            None => Ok(()),
+        }
+    }
+
+    /// Replaces the target with everything written so far.
+    ///
+    /// ## Errors
+    ///
+    /// This function will return an error if the file cannot be synced or
+    /// renamed, and the target is unchanged then, or if the target can't be
+    /// overwritten.
+    pub fn commit(self) -> io::Result<()> {
+        // everything has been written to the target already
+        let Some(temp) = &self.temp else {
+            return Ok(());
+        };
+        self.file.sync_all()?;
+        match fs::metadata(&self.target) {
+            Ok(metadata) if !self.can_replace(&metadata) => self.overwrite(temp),
+            // once renamed, the removal on drop fails harmlessly
+            _ => fs::rename(temp, &self.target),
+        }
+    }
+
+    /// Whether renaming over the target keeps everything that refers to it,
+    /// giving the temporary file the target's owner if need be. Only a
+    /// regular file can be replaced.
+    #[cfg(unix)]
+    fn can_replace(&self, target: &fs::Metadata) -> bool {
+        use std::os::unix::fs::MetadataExt;
+
+        target.is_file()
+            && target.nlink() <= 1
+            && std::os::unix::fs::fchown(&self.file, Some(target.uid()), Some(target.gid())).is_ok()
+    }
+
+    /// Elsewhere, only FIFOs and devices can't be renamed over.
+    #[cfg(not(unix))]
+    fn can_replace(&self, target: &fs::Metadata) -> bool {
+        target.is_file()
+    }
+
+    /// Writes the content over the target itself.
+    fn overwrite(&self, temp: &Path) -> io::Result<()> {
+        let mut target = OpenOptions::new().write(true).open(&self.target)?;
+        let len = io::copy(&mut File::open(temp)?, &mut target)?;
+        // a FIFO or device has no length to cut, nor anything to sync
+        if !target.metadata()?.is_file() {
+            return Ok(());
+        }
+        target.set_len(len)?;
+        target.sync_all()
+    }
+}
+
+impl Write for AtomicFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.file.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+impl Drop for AtomicFile {
+    fn drop(&mut self) {
+        if let Some(temp) = &self.temp {
+            let _ = fs::remove_file(temp);
+        }
+    }
+}
+
+#[cfg(unix)]
+impl std::os::fd::AsFd for AtomicFile {
+    fn as_fd(&self) -> std::os::fd::BorrowedFd<'_> {
+        self.file.as_fd()
+    }
+}
+
+#[cfg(not(target_os = "wasi"))]
+fn set_permissions(file: &File, permissions: Permissions) -> io::Result<()> {
+    file.set_permissions(permissions)
+}
+
+/// WASI has no permission bits to preserve.
+#[cfg(target_os = "wasi")]
+fn set_permissions(_file: &File, _permissions: Permissions) -> io::Result<()> {
+    Ok(())
+}
+
+/// Distinguishes the temporary files of concurrent processes.
+#[cfg(not(target_os = "wasi"))]
+fn process_tag() -> u32 {
+    std::process::id()
+}
+
+/// WASI has no process IDs, so the clock stands in for one.
+#[cfg(target_os = "wasi")]
+fn process_tag() -> u32 {
+    use std::time::SystemTime;
+
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.subsec_nanos())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+    use std::io::Cursor;
+    use std::io::Write;
+
+    use super::AtomicFile;
+    use super::FileTail;
+    use super::TAIL_BLOCK;
+    use super::check_file;
+    use super::check_seekable;
+    use super::process_file_in_place;
+    use crate::LineEnding;
+    use crate::Options;
+
+    #[test]
+    fn test_check_file() {
+        let path = env::temp_dir().join(format!("nln-test-check-file-{}", std::process::id()));
+        let newlines = "\r\n".repeat(TAIL_BLOCK);
+        for (content, trailing_start) in [
+            (String::new(), 0),
+            ("abc".to_string(), 3),
+            (format!("a\nb{newlines}"), 3),
+            (newlines.clone(), 0),
+        ] {
+            fs::write(&path, &content).unwrap();
+            let tail = check_file(&path).unwrap();
+            assert_eq!(
+                tail,
+                FileTail {
+                    len: content.len() as u64,
+                    trailing_start,
+                }
+            );
+        }
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_check_seekable() {
+        let mut cursor = Cursor::new(b"\n\na\n\r\n".as_slice());
+        cursor.set_position(2);
+        assert_eq!(
+            check_seekable(&mut cursor).unwrap(),
+            FileTail {
+                len: 6,
+                trailing_start: 3,
+            }
+        );
+        assert_eq!(cursor.position(), 2);
+
+        // nothing but newlines after the position
+        cursor.set_position(3);
+        assert_eq!(check_seekable(&mut cursor).unwrap().trailing_start, 3);
+    }
+
+    #[test]
+    fn test_process_file_in_place() {
+        let path = env::temp_dir().join(format!("nln-test-in-place-{}", std::process::id()));
+        for (options, expected) in [
+            (Options::builder().keep_trailing(1).build(), "a\r\nb\r\n"),
+            (
+                Options::builder().line_ending(LineEnding::Lf).build(),
+                "a\nb",
+            ),
+        ] {
+            fs::write(&path, "a\r\nb\r\n\n").unwrap();
+            let stats = process_file_in_place(&path, &options).unwrap();
+            assert_eq!(fs::read_to_string(&path).unwrap(), expected);
+            assert_eq!(stats.bytes_out, expected.len() as u64);
+        }
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_truncate_matches_processing() {
+        let path = env::temp_dir().join(format!("nln-test-truncate-{}", std::process::id()));
+        // the CRLF straddles blocks as they are read from the trailing start
+        let long = format!("a{}\r\n\n\r", "\r\n".repeat(TAIL_BLOCK / 2 - 1));
+        for content in ["", "abc", "\n\r\n", "a\r\n\r\r\n\n", &long] {
+            for options in [
+                Options::default(),
+                Options::builder().keep_trailing(2).build(),
+                Options::builder().keep_trailing(TAIL_BLOCK / 2).build(),
+                Options::builder().strip_trailing(false).build(),
+            ] {
+                fs::write(&path, content).unwrap();
+                let stats = process_file_in_place(&path, &options).unwrap();
+                let mut expected = Vec::new();
+                let expected_stats =
+                    crate::process(content.as_bytes(), &mut expected, &options).unwrap();
+                assert_eq!(
+                    fs::read(&path).unwrap(),
+                    expected,
+                    "{content:?} {options:?}"
+                );
+                assert_eq!(
+                    (stats.bytes_in, stats.bytes_out),
+                    (expected_stats.bytes_in, expected_stats.bytes_out)
+                );
+                assert_eq!(
+                    (stats.lf_stripped, stats.cr_stripped),
+                    (expected_stats.lf_stripped, expected_stats.cr_stripped)
+                );
+            }
+        }
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_links_are_kept() {
+        let dir = env::temp_dir().join(format!("nln-test-links-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir(&dir).unwrap();
+        let (file, symlink, hard_link) = (dir.join("file"), dir.join("symlink"), dir.join("hard"));
+        fs::write(&file, "a\r\n\n").unwrap();
+        std::os::unix::fs::symlink("file", &symlink).unwrap();
+        fs::hard_link(&file, &hard_link).unwrap();
+
+        // rewritten rather than truncated
+        let options = Options::builder().line_ending(LineEnding::Lf).build();
+        process_file_in_place(&symlink, &options).unwrap();
+        assert!(fs::symlink_metadata(&symlink).unwrap().is_symlink());
+        assert_eq!(fs::read(&file).unwrap(), b"a");
+        assert_eq!(fs::read(&hard_link).unwrap(), b"a");
+
+        fs::write(&file, "b\r\n\n").unwrap();
+        process_file_in_place(&hard_link, &options).unwrap();
+        assert_eq!(fs::read(&file).unwrap(), b"b");
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 3);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_fifo_is_kept() {
+        use std::os::unix::fs::FileTypeExt;
+        use std::process::Command;
+        use std::thread;
+
+        let dir = env::temp_dir().join(format!("nln-test-fifo-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir(&dir).unwrap();
+        let fifo = dir.join("fifo");
+        let mkfifo = || {
+            assert!(
+                Command::new("mkfifo")
+                    .arg(&fifo)
+                    .status()
+                    .unwrap()
+                    .success()
+            )
+        };
+        let read = || {
+            let fifo = fifo.clone();
+            thread::spawn(move || fs::read(fifo).unwrap())
+        };
+
+        // written directly
+        mkfifo();
+        let reader = read();
+        let mut file = AtomicFile::create(&fifo).unwrap();
+        assert_eq!(file.temp_path(), None);
+        file.write_all(b"a").unwrap();
+        file.commit().unwrap();
+        assert!(fs::metadata(&fifo).unwrap().file_type().is_fifo());
+        assert_eq!(reader.join().unwrap(), b"a");
+
+        // a FIFO in place of the file by the time of the commit
+        fs::remove_file(&fifo).unwrap();
+        fs::write(&fifo, b"").unwrap();
+        let mut file = AtomicFile::create(&fifo).unwrap();
+        fs::remove_file(&fifo).unwrap();
+        mkfifo();
+        let reader = read();
+        file.write_all(b"b").unwrap();
+        file.commit().unwrap();
+        assert!(fs::metadata(&fifo).unwrap().file_type().is_fifo());
+        assert_eq!(reader.join().unwrap(), b"b");
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
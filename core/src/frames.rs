@@ -0,0 +1,101 @@
+//! A [`Bytes`] adapter over [`Stripper`].
+
+use bytes::Bytes;
+
+use crate::Error;
+use crate::Options;
+use crate::Stats;
+use crate::Stripper;
+
+/// A [`Stripper`] that takes and returns [`Bytes`] frames, as used by
+/// hyper and tower bodies.
+///
+/// Output that is part of a pushed frame, which is all of it apart from
+/// terminators held back from earlier frames, is returned as a slice of that
+/// frame without copying.
+///
+/// ```
+/// use bytes::Bytes;
+///
+/// let mut stripper = nln_core::BytesStripper::new(nln_core::Options::default());
+/// let mut out = Vec::new();
+/// for frame in [Bytes::from("hello\n"), Bytes::from("\nworld\n\n")] {
+///     out.extend(stripper.push(&frame)?);
+/// }
+/// out.extend(stripper.finish());
+/// assert_eq!(out.concat(), b"hello\n\nworld");
+/// # Ok::<(), nln_core::Error>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct BytesStripper {
+    stripper: Stripper,
+}
+
+impl BytesStripper {
+    /// Creates a stripper for a new stream.
+    pub fn new(options: Options) -> Self {
+        Self {
+            stripper: Stripper::new(options),
+        }
+    }
+
+    /// Processes the next frame of input, returning the frames of output that
+    /// are known so far.
+    ///
+    /// ## Errors
+    ///
+    /// This function will return [`Error::Limit`] if more terminators are held
+    /// back than [`OptionsBuilder::max_pending_bytes`] allows.
+    ///
+    /// [`OptionsBuilder::max_pending_bytes`]: crate::OptionsBuilder::max_pending_bytes
+    pub fn push<'a>(
+        &'a mut self,
+        frame: &'a Bytes,
+    ) -> Result<impl Iterator<Item = Bytes> + 'a, Error> {
+        let pieces = self.stripper.push(frame)?;
+        Ok(pieces.map(|piece| slice_or_copy(frame, piece)))
+    }
+
+    /// Signals the end of input, returning the frames of output that remain.
+    pub fn finish(&mut self) -> impl Iterator<Item = Bytes> + '_ {
+        self.stripper.finish().map(Bytes::copy_from_slice)
+    }
+
+    /// Statistics about the stream so far.
+    pub fn stats(&self) -> Stats {
+        self.stripper.stats()
+    }
+}
+
+/// Shares `piece` with `frame` if it lies within it, and copies it otherwise.
+fn slice_or_copy(frame: &Bytes, piece: &[u8]) -> Bytes {
+    let frame_range = frame.as_ptr_range();
+    let piece_range = piece.as_ptr_range();
+    if frame_range.start <= piece_range.start && piece_range.end <= frame_range.end {
+        frame.slice_ref(piece)
+    } else {
+        Bytes::copy_from_slice(piece)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+
+    use crate::BytesStripper;
+    use crate::Options;
+
+    #[test]
+    fn test_shares_unchanged_content() {
+        let mut stripper = BytesStripper::new(Options::default());
+        let frame = Bytes::from("abc\n");
+        let out: Vec<_> = stripper.push(&frame).unwrap().collect();
+        assert_eq!(out, ["abc"]);
+        assert_eq!(out[0].as_ptr(), frame.as_ptr());
+
+        let frame = Bytes::from("\ndef\n\n");
+        let out: Vec<_> = stripper.push(&frame).unwrap().collect();
+        assert_eq!(out.concat(), b"\n\ndef");
+        assert!(stripper.finish().next().is_none());
+    }
+}
@@ -0,0 +1,103 @@
+//! Processing [`futures_io`] streams.
+
+use std::io::ErrorKind;
+
+use futures_io::AsyncBufRead;
+use futures_io::AsyncWrite;
+use futures_util::AsyncBufReadExt;
+use futures_util::AsyncWriteExt;
+
+use crate::Error;
+use crate::Flush;
+use crate::Options;
+use crate::Stats;
+use crate::Stripper;
+
+/// Moves bytes from the async input reader to the async output writer,
+/// transforming newlines as configured by `options`, like
+/// [`process`](crate::process).
+///
+/// Works with any runtime implementing the `futures-io` traits, such as smol
+/// or async-std. The writer is flushed as [`OptionsBuilder::flush`] says.
+///
+/// [`OptionsBuilder::flush`]: crate::OptionsBuilder::flush
+///
+/// ## Errors
+///
+/// This function will return [`Error::Read`] if the reader cannot be read and
+/// [`Error::Write`] if the writer cannot be written to.
+#[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+pub async fn process_async(
+    mut i: impl AsyncBufRead + Unpin,
+    o: &mut (impl AsyncWrite + Unpin),
+    options: &Options,
+) -> Result<Stats, Error> {
+    let mut stripper = Stripper::new(options.clone());
+    loop {
+        let buf = match i.fill_buf().await {
+            Ok(buf) => buf,
+            Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+            Err(err) => return Err(Error::Read(err)),
+        };
+        if buf.is_empty() {
+            break;
+        }
+        let n = buf.len();
+        event!(trace, bytes = n, "read chunk");
+        let pieces = match stripper.push(buf) {
+            Err(Error::Cancelled) => {
+                if options.flush != Flush::Never {
+                    o.flush().await.map_err(Error::Write)?;
+                }
+                return Err(Error::Cancelled);
+            }
+            pieces => pieces?,
+        };
+        let mut produced = false;
+        for piece in pieces {
+            o.write_all(piece).await.map_err(Error::Write)?;
+            produced |= !piece.is_empty();
+        }
+        if produced && options.flush == Flush::Line {
+            o.flush().await.map_err(Error::Write)?;
+        }
+        i.consume_unpin(n);
+    }
+    for piece in stripper.finish() {
+        o.write_all(piece).await.map_err(Error::Write)?;
+    }
+    if options.flush != Flush::Never {
+        event!(trace, "flushing output");
+        o.flush().await.map_err(Error::Write)?;
+    }
+    let stats = stripper.stats();
+    event!(debug, ?stats, "finished processing");
+    Ok(stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::pin::pin;
+    use std::task::Context;
+    use std::task::Poll;
+    use std::task::Waker;
+
+    use super::process_async;
+    use crate::Options;
+
+    #[test]
+    fn test_process_async() {
+        let mut out = Vec::new();
+        let options = Options::default();
+        let stats = {
+            let future = pin!(process_async(b"\na\r\n\r\n".as_slice(), &mut out, &options));
+            // in-memory readers and writers are always ready
+            let Poll::Ready(stats) = future.poll(&mut Context::from_waker(Waker::noop())) else {
+                panic!("in-memory processing should not be pending");
+            };
+            stats.unwrap()
+        };
+        assert_eq!(stats.bytes_out, 2);
+        assert_eq!(out, b"\na");
+    }
+}
@@ -0,0 +1,819 @@
+//! Remove trailing newlines and carriage returns from a stream.
+//!
+//! This is the core of `nln`, without the command-line interface and without
+//! required dependencies. The `nln` crate re-exports all of it. Newlines
+//! within the content are preserved; only the run of `\n` and `\r` bytes at
+//! the very end of the input is dropped.
+//!
+//! ```
+//! let mut out = Vec::new();
+//! nln_core::strip_trailing_newlines(b"hello\n\nworld\r\n\n".as_slice(), &mut out)?;
+//! assert_eq!(out, b"hello\n\nworld");
+//! # Ok::<(), std::io::Error>(())
+//! ```
+//!
+//! Everything public is exported from the crate root and follows semantic
+//! versioning.
+
+use std::io::BufRead;
+use std::io::BufReader;
+use std::io::ErrorKind;
+use std::io::IoSlice;
+use std::io::Read;
+use std::io::Result;
+use std::io::Write;
+
+use crate::coalesce::Coalescer;
+
+pub use crate::check::CheckResult;
+pub use crate::check::check;
+pub use crate::check::detect_eol;
+#[cfg(feature = "tokio-util")]
+pub use crate::codec::StripTrailingNewlinesCodec;
+pub use crate::error::Error;
+pub use crate::ext::StripTrailingNewlinesExt;
+pub use crate::ext::chomp;
+pub use crate::ext::chomp_bytes;
+pub use crate::file::AtomicFile;
+pub use crate::file::FileTail;
+pub use crate::file::check_file;
+pub use crate::file::check_seekable;
+pub use crate::file::process_file_in_place;
+#[cfg(feature = "bytes")]
+pub use crate::frames::BytesStripper;
+#[cfg(feature = "futures-io")]
+pub use crate::futures::process_async;
+pub use crate::lines::Lines;
+pub use crate::options::DEFAULT_BUFFER_SIZE;
+pub use crate::options::Flush;
+pub use crate::options::LineEnding;
+pub use crate::options::Options;
+pub use crate::options::OptionsBuilder;
+pub use crate::options::Stage;
+pub use crate::policy::AsciiPolicy;
+pub use crate::policy::CrLfPolicy;
+pub use crate::policy::LfPolicy;
+pub use crate::policy::MAX_TERMINATOR_LEN;
+pub use crate::policy::NewlinePolicy;
+pub use crate::policy::PredicatePolicy;
+pub use crate::policy::Terminator;
+pub use crate::policy::TerminatorMatch;
+pub use crate::policy::UnicodePolicy;
+pub use crate::policy::is_newline;
+pub use crate::reader::StrippedReader;
+pub use crate::slice::process_bytes;
+pub use crate::slice::process_str;
+pub use crate::slice::strip_trailing_newlines_bytes;
+pub use crate::slice::strip_trailing_newlines_str;
+pub use crate::stats::Stats;
+#[cfg(feature = "stream")]
+pub use crate::stream::StripTrailingNewlinesStream;
+pub use crate::stripper::Stripper;
+pub use crate::text::StripTrailingNewlinesFmt;
+pub use crate::thread::process_pipelined;
+pub use crate::thread::spawn_strip;
+pub use crate::writer::EnsureTrailingNewline;
+pub use crate::writer::LineNormalizer;
+pub use crate::writer::StripTrailingNewlines;
+
+/// Emits a [`tracing`](https://docs.rs/tracing) event if the `tracing`
+/// feature is enabled.
+macro_rules! event {
+    ($level:ident, $($arg:tt)*) => {
+        #[cfg(feature = "tracing")]
+        tracing::$level!($($arg)*);
+    };
+}
+
+mod check;
+mod coalesce;
+#[cfg(feature = "tokio-util")]
+mod codec;
+mod error;
+mod ext;
+mod file;
+#[cfg(feature = "bytes")]
+mod frames;
+#[cfg(feature = "futures-io")]
+mod futures;
+mod lines;
+mod options;
+mod policy;
+#[cfg(kani)]
+mod proofs;
+mod reader;
+mod simd;
+mod slice;
+mod stats;
+#[cfg(feature = "stream")]
+mod stream;
+mod stripper;
+#[cfg(test)]
+mod testing;
+mod text;
+mod thread;
+#[cfg(feature = "wasm")]
+mod wasm;
+mod writer;
+
+/// Moves bytes from the input reader to the output writer, removing any
+/// trailing newlines.
+///
+/// Newlines are held back until more content arrives, as runs of identical
+/// ones, so even a huge run of newlines takes little memory. The writer is
+/// flushed before returning.
+///
+/// ## Errors
+///
+/// This function will return an error if the reader cannot be read or the
+/// writer cannot be written to.
+pub fn strip_trailing_newlines(i: impl BufRead, o: &mut impl Write) -> Result<()> {
+    process(i, o, &Options::default())?;
+    Ok(())
+}
+
+/// Moves bytes from the input reader to the output writer, transforming
+/// newlines as configured by `options`, and returns [`Stats`] about what was
+/// done.
+///
+/// With [`Options::default`] this behaves exactly like
+/// [`strip_trailing_newlines`]. Reads and writes interrupted by a signal are
+/// retried. The writer is flushed before returning, unless
+/// [`OptionsBuilder::flush`] says otherwise.
+///
+/// ## Errors
+///
+/// This function will return [`Error::Read`] if the reader cannot be read and
+/// [`Error::Write`] if the writer cannot be written to. The errors of
+/// [`Stripper::push`], such as [`Error::Cancelled`], are passed on.
+#[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+pub fn process(
+    mut i: impl BufRead,
+    o: &mut impl Write,
+    options: &Options,
+) -> std::result::Result<Stats, Error> {
+    let mut stripper = Stripper::new(options.clone());
+    let mut coalescer = Coalescer::new(options.coalesce);
+    loop {
+        let buf = match i.fill_buf() {
+            Ok(buf) => buf,
+            Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+            Err(err) => return Err(read_error(o, &mut coalescer, err)),
+        };
+        if buf.is_empty() {
+            break;
+        }
+        let n = buf.len();
+        event!(trace, bytes = n, "read chunk");
+        let bytes_out = stripper.stats().bytes_out;
+        let pieces = stripper
+            .push(buf)
+            .map_err(|err| flush_if_cancelled(o, &mut coalescer, options, err))?;
+        coalescer.write(o, pieces).map_err(Error::Write)?;
+        if options.flush == Flush::Line && stripper.stats().bytes_out > bytes_out {
+            flush(o, &mut coalescer).map_err(Error::Write)?;
+        }
+        i.consume(n);
+    }
+    coalescer
+        .write(o, stripper.finish())
+        .map_err(Error::Write)?;
+    finish_output(o, &mut coalescer, options).map_err(Error::Write)?;
+    let stats = stripper.stats();
+    event!(debug, ?stats, "finished processing");
+    Ok(stats)
+}
+
+/// Like [`process`], but for an unbuffered reader such as a [`File`] or a
+/// socket, which it reads in chunks of [`Options::buffer_size`] bytes.
+///
+/// [`File`]: std::fs::File
+///
+/// ## Errors
+///
+/// This function will return [`Error::Read`] if the reader cannot be read and
+/// [`Error::Write`] if the writer cannot be written to.
+pub fn process_read(
+    i: impl Read,
+    o: &mut impl Write,
+    options: &Options,
+) -> std::result::Result<Stats, Error> {
+    process(
+        BufReader::with_capacity(options.buffer_size(), i),
+        o,
+        options,
+    )
+}
+
+/// Like [`process`], but takes the input as chunks that are already in
+/// memory, such as messages from a queue or a network stream.
+///
+/// ```
+/// let chunks = [Ok(b"abc\n".to_vec()), Ok(b"\n".to_vec())];
+/// let mut out = Vec::new();
+/// nln_core::process_chunks(chunks, &mut out, &nln_core::Options::default())?;
+/// assert_eq!(out, b"abc");
+/// # Ok::<(), nln_core::Error>(())
+/// ```
+///
+/// ## Errors
+///
+/// This function will return [`Error::Read`] with the first error yielded by
+/// `chunks` and [`Error::Write`] if the writer cannot be written to.
+#[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+pub fn process_chunks<B: AsRef<[u8]>>(
+    chunks: impl IntoIterator<Item = Result<B>>,
+    o: &mut impl Write,
+    options: &Options,
+) -> std::result::Result<Stats, Error> {
+    let mut stripper = Stripper::new(options.clone());
+    let mut coalescer = Coalescer::new(options.coalesce);
+    for chunk in chunks {
+        let chunk = chunk.map_err(|err| read_error(o, &mut coalescer, err))?;
+        let chunk = chunk.as_ref();
+        event!(trace, bytes = chunk.len(), "read chunk");
+        let bytes_out = stripper.stats().bytes_out;
+        let pieces = stripper
+            .push(chunk)
+            .map_err(|err| flush_if_cancelled(o, &mut coalescer, options, err))?;
+        coalescer.write(o, pieces).map_err(Error::Write)?;
+        if options.flush == Flush::Line && stripper.stats().bytes_out > bytes_out {
+            flush(o, &mut coalescer).map_err(Error::Write)?;
+        }
+    }
+    coalescer
+        .write(o, stripper.finish())
+        .map_err(Error::Write)?;
+    finish_output(o, &mut coalescer, options).map_err(Error::Write)?;
+    let stats = stripper.stats();
+    event!(debug, ?stats, "finished processing");
+    Ok(stats)
+}
+
+/// Moves bytes from the input reader to the output writer, removing any
+/// trailing newlines.
+///
+/// ## Errors
+///
+/// This function will return an error if the reader cannot be read or the
+/// writer cannot be written to.
+#[deprecated(since = "1.1.0", note = "renamed to `strip_trailing_newlines`")]
+pub fn snickerdoodle(i: impl BufRead, o: &mut impl Write) -> Result<()> {
+    strip_trailing_newlines(i, o)
+}
+
+/// Writes out the output produced before a cancellation, all of which is
+/// final.
+fn flush_if_cancelled(
+    o: &mut impl Write,
+    coalescer: &mut Coalescer,
+    options: &Options,
+    err: Error,
+) -> Error {
+    match err {
+        Error::Cancelled => {
+            finish_output(o, coalescer, options).map_or_else(Error::Write, |()| Error::Cancelled)
+        }
+        err => err,
+    }
+}
+
+/// Writes out the output gathered before a read error, so that the content
+/// that was read isn't lost with it.
+fn read_error(o: &mut impl Write, coalescer: &mut Coalescer, err: std::io::Error) -> Error {
+    // the read error is the one worth reporting
+    let _ = coalescer.finish(o);
+    Error::Read(err)
+}
+
+/// Writes out what the coalescer gathered and flushes.
+fn flush(o: &mut impl Write, coalescer: &mut Coalescer) -> Result<()> {
+    coalescer.finish(o)?;
+    o.flush()
+}
+
+/// Writes out what the coalescer gathered and flushes unless
+/// [`Flush::Never`] says not to.
+fn finish_output(o: &mut impl Write, coalescer: &mut Coalescer, options: &Options) -> Result<()> {
+    if options.flush == Flush::Never {
+        return coalescer.finish(o);
+    }
+    event!(trace, "flushing output");
+    flush(o, coalescer)
+}
+
+/// How many pieces of output go into one vectored write.
+const WRITE_BATCH: usize = 64;
+
+/// Writes all `pieces`, batching them into vectored writes so held back
+/// newlines and the content after them go out together.
+fn write_pieces<'a>(o: &mut impl Write, pieces: impl Iterator<Item = &'a [u8]>) -> Result<()> {
+    let mut batch = [IoSlice::new(&[]); WRITE_BATCH];
+    let mut len = 0;
+    for piece in pieces {
+        batch[len] = IoSlice::new(piece);
+        len += 1;
+        if len == WRITE_BATCH {
+            write_all_vectored(o, &mut batch[..len])?;
+            len = 0;
+        }
+    }
+    write_all_vectored(o, &mut batch[..len])
+}
+
+/// Like the unstable [`Write::write_all_vectored`].
+fn write_all_vectored(o: &mut impl Write, mut slices: &mut [IoSlice<'_>]) -> Result<()> {
+    // drops leading empty slices, which would make the write return 0
+    IoSlice::advance_slices(&mut slices, 0);
+    while !slices.is_empty() {
+        match o.write_vectored(slices) {
+            Ok(0) => return Err(ErrorKind::WriteZero.into()),
+            Ok(n) => IoSlice::advance_slices(&mut slices, n),
+            Err(err) if err.kind() == ErrorKind::Interrupted => {}
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Error;
+    use super::Flush;
+    use super::Options;
+    use std::sync::Arc;
+    use std::sync::atomic::AtomicBool;
+    use std::sync::atomic::Ordering;
+
+    use super::StripTrailingNewlines;
+    use super::process;
+    use super::process_bytes;
+    use super::process_chunks;
+    use super::process_read;
+    use super::strip_trailing_newlines;
+    use crate::testing::FailingReader;
+    use crate::testing::FaultyWriter;
+    use crate::testing::Interrupting;
+    use std::io::BufReader;
+    use std::io::ErrorKind;
+    use std::io::Write;
+
+    #[test]
+    fn test_empty() {
+        let mut buf = Vec::new();
+        strip_trailing_newlines(b"".as_slice(), &mut buf).unwrap();
+        assert_eq!(buf, b"");
+    }
+
+    #[test]
+    fn test_no_change() {
+        let mut buf = Vec::new();
+        strip_trailing_newlines(b"abc".as_slice(), &mut buf).unwrap();
+        assert_eq!(buf, b"abc");
+    }
+
+    #[test]
+    fn test_trailing_nl() {
+        let mut buf = Vec::new();
+        strip_trailing_newlines(b"\n".as_slice(), &mut buf).unwrap();
+        assert_eq!(str::from_utf8(&buf).unwrap(), "");
+
+        buf.clear();
+        strip_trailing_newlines(b"abc\n".as_slice(), &mut buf).unwrap();
+        assert_eq!(str::from_utf8(&buf).unwrap(), "abc");
+    }
+
+    #[test]
+    fn test_trailing_crlf() {
+        let mut buf = Vec::new();
+        strip_trailing_newlines(b"abc\r\n".as_slice(), &mut buf).unwrap();
+        assert_eq!(str::from_utf8(&buf).unwrap(), "abc");
+    }
+
+    #[test]
+    fn test_trailing_cr() {
+        let mut buf = Vec::new();
+        strip_trailing_newlines(b"abc\r".as_slice(), &mut buf).unwrap();
+        assert_eq!(str::from_utf8(&buf).unwrap(), "abc");
+    }
+
+    #[test]
+    fn test_trailing_multi_nl() {
+        let mut buf = Vec::new();
+        strip_trailing_newlines(b"abc\n\n".as_slice(), &mut buf).unwrap();
+        assert_eq!(str::from_utf8(&buf).unwrap(), "abc");
+
+        buf.clear();
+        strip_trailing_newlines(b"abc\n\n\n".as_slice(), &mut buf).unwrap();
+        assert_eq!(str::from_utf8(&buf).unwrap(), "abc");
+    }
+
+    #[test]
+    fn test_trailing_multi_crlf() {
+        let mut buf = Vec::new();
+        strip_trailing_newlines(b"abc\r\n\r\n".as_slice(), &mut buf).unwrap();
+        assert_eq!(str::from_utf8(&buf).unwrap(), "abc");
+    }
+
+    #[test]
+    fn test_only_nl() {
+        let mut buf = Vec::new();
+        strip_trailing_newlines(b"\n\n\n".as_slice(), &mut buf).unwrap();
+        assert_eq!(str::from_utf8(&buf).unwrap(), "");
+    }
+
+    #[test]
+    fn test_only_crlf() {
+        let mut buf = Vec::new();
+        strip_trailing_newlines(b"\r\n\r\n".as_slice(), &mut buf).unwrap();
+        assert_eq!(str::from_utf8(&buf).unwrap(), "");
+    }
+
+    #[test]
+    fn test_only_cr() {
+        let mut buf = Vec::new();
+        strip_trailing_newlines(b"\r\r\r".as_slice(), &mut buf).unwrap();
+        assert_eq!(str::from_utf8(&buf).unwrap(), "");
+    }
+
+    #[test]
+    fn test_leading_nl() {
+        let mut buf = Vec::new();
+        strip_trailing_newlines(b"\nabc".as_slice(), &mut buf).unwrap();
+        assert_eq!(str::from_utf8(&buf).unwrap(), "\nabc");
+    }
+
+    #[test]
+    fn test_leading_multi_nl() {
+        let mut buf = Vec::new();
+        strip_trailing_newlines(b"\n\nabc".as_slice(), &mut buf).unwrap();
+        assert_eq!(str::from_utf8(&buf).unwrap(), "\n\nabc");
+    }
+
+    #[test]
+    fn test_leading_crlf() {
+        let mut buf = Vec::new();
+        strip_trailing_newlines(b"\r\nabc".as_slice(), &mut buf).unwrap();
+        assert_eq!(str::from_utf8(&buf).unwrap(), "\r\nabc");
+    }
+
+    #[test]
+    fn test_leading_trailing() {
+        let mut buf = Vec::new();
+        strip_trailing_newlines(b"\nabc\n".as_slice(), &mut buf).unwrap();
+        assert_eq!(str::from_utf8(&buf).unwrap(), "\nabc");
+    }
+
+    #[test]
+    fn test_leading_trailing_multi() {
+        let mut buf = Vec::new();
+        strip_trailing_newlines(b"\n\nabc\n\n".as_slice(), &mut buf).unwrap();
+        assert_eq!(str::from_utf8(&buf).unwrap(), "\n\nabc");
+    }
+
+    #[test]
+    fn test_mixed_trailing() {
+        let mut buf = Vec::new();
+        strip_trailing_newlines(b"abc\n\r\n".as_slice(), &mut buf).unwrap();
+        assert_eq!(str::from_utf8(&buf).unwrap(), "abc");
+    }
+
+    #[test]
+    fn test_mixed_trailing_types() {
+        let mut buf = Vec::new();
+        strip_trailing_newlines(b"abc\r\n\n\r".as_slice(), &mut buf).unwrap();
+        assert_eq!(str::from_utf8(&buf).unwrap(), "abc");
+    }
+
+    #[test]
+    fn test_middle_nl() {
+        let mut buf = Vec::new();
+        strip_trailing_newlines(b"ab\nc\n".as_slice(), &mut buf).unwrap();
+        assert_eq!(str::from_utf8(&buf).unwrap(), "ab\nc");
+
+        buf.clear();
+        strip_trailing_newlines(b"ab\n\nc\n".as_slice(), &mut buf).unwrap();
+        assert_eq!(str::from_utf8(&buf).unwrap(), "ab\n\nc");
+    }
+
+    #[test]
+    fn test_mixed_in_content() {
+        let mut buf = Vec::new();
+        strip_trailing_newlines(b"a\rb\nc\r\nd\n".as_slice(), &mut buf).unwrap();
+        assert_eq!(str::from_utf8(&buf).unwrap(), "a\rb\nc\r\nd");
+    }
+
+    #[test]
+    fn test_large_trailing() {
+        let mut buf = Vec::new();
+        let mut input = "x".repeat(100000);
+        input.push_str("\n\n\n");
+        strip_trailing_newlines(input.as_bytes(), &mut buf).unwrap();
+        assert_eq!(str::from_utf8(&buf).unwrap(), "x".repeat(100000));
+    }
+
+    #[test]
+    fn test_large_middle() {
+        let mut buf = Vec::new();
+        let mut input = "x".repeat(50000);
+        input.push_str("\n\n");
+        input.push_str(&"y".repeat(50000));
+        input.push_str("\n\n\n");
+        strip_trailing_newlines(input.as_bytes(), &mut buf).unwrap();
+        let expected = format!("{}\n\n{}", "x".repeat(50000), "y".repeat(50000));
+        assert_eq!(str::from_utf8(&buf).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_process_error_side() {
+        struct Failing;
+
+        impl std::io::Read for Failing {
+            fn read(&mut self, _: &mut [u8]) -> std::io::Result<usize> {
+                Err(std::io::ErrorKind::Other.into())
+            }
+        }
+
+        impl std::io::Write for Failing {
+            fn write(&mut self, _: &[u8]) -> std::io::Result<usize> {
+                Err(std::io::ErrorKind::Other.into())
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let options = Options::default();
+        let mut buf = Vec::new();
+        let err = process(std::io::BufReader::new(Failing), &mut buf, &options).unwrap_err();
+        assert!(matches!(err, Error::Read(_)));
+        let err = process(b"abc".as_slice(), &mut Failing, &options).unwrap_err();
+        assert!(matches!(err, Error::Write(_)));
+    }
+
+    #[test]
+    fn test_process_chunks() {
+        let options = Options::builder().keep_trailing(1).build();
+        let chunks = ["a\r", "\nb\n", "", "\r\n\n"].map(Ok);
+        let mut buf = Vec::new();
+        let stats = process_chunks(chunks, &mut buf, &options).unwrap();
+        assert_eq!(buf, b"a\r\nb\n");
+        assert_eq!(stats.chunks, 4);
+
+        let chunks = [Ok("abc"), Err(std::io::ErrorKind::Other.into())];
+        let err = process_chunks(chunks, &mut buf, &options).unwrap_err();
+        assert!(matches!(err, Error::Read(_)));
+    }
+
+    #[test]
+    fn test_coalesce_writes() {
+        /// Counts the calls that write something.
+        #[derive(Default)]
+        struct Counting {
+            inner: Vec<u8>,
+            writes: usize,
+        }
+
+        impl std::io::Write for Counting {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                self.writes += 1;
+                self.inner.write(buf)
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let lines = vec!["line\n"; 1000];
+        let mut expected = lines.concat();
+        expected.pop();
+        for (threshold, max_writes) in [(0, 2000), (100, 200), (1 << 20, 1)] {
+            let options = Options::builder().coalesce_writes(threshold).build();
+            let mut output = Counting::default();
+            process_chunks(lines.iter().map(Ok), &mut output, &options).unwrap();
+            assert_eq!(output.inner, expected.as_bytes(), "{threshold}");
+            assert!(
+                output.writes <= max_writes,
+                "{threshold}: {}",
+                output.writes
+            );
+        }
+    }
+
+    #[test]
+    fn test_flush() {
+        /// Counts flushes.
+        #[derive(Default)]
+        struct Flushes {
+            inner: Vec<u8>,
+            flushes: usize,
+        }
+
+        impl std::io::Write for Flushes {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                self.inner.write(buf)
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                self.flushes += 1;
+                Ok(())
+            }
+        }
+
+        // the last two chunks produce no output
+        let chunks = ["a\n", "b\n", "\n", ""];
+        for (flush, flushes) in [(Flush::Line, 3), (Flush::End, 1), (Flush::Never, 0)] {
+            for threshold in [0, 1024] {
+                let options = Options::builder()
+                    .flush(flush)
+                    .coalesce_writes(threshold)
+                    .build();
+                let mut output = Flushes::default();
+                process_chunks(chunks.map(Ok), &mut output, &options).unwrap();
+                assert_eq!(output.inner, b"a\nb", "{flush:?}");
+                assert_eq!(output.flushes, flushes, "{flush:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_cancel() {
+        for threshold in [0, 1024] {
+            let cancel = Arc::new(AtomicBool::new(false));
+            let options = Options::builder()
+                .cancel_flag(Arc::clone(&cancel))
+                .coalesce_writes(threshold)
+                .build();
+            let chunks = ["a\n", "b\n", "c"].into_iter().map(|chunk| {
+                if chunk == "c" {
+                    cancel.store(true, Ordering::Relaxed);
+                }
+                Ok(chunk)
+            });
+            let mut buf = Vec::new();
+            let err = process_chunks(chunks, &mut buf, &options).unwrap_err();
+            assert!(matches!(err, Error::Cancelled));
+            // what was gathered is written out too
+            assert_eq!(buf, b"a\nb", "{threshold}");
+        }
+    }
+
+    #[test]
+    fn test_process_read() {
+        let options = Options::builder().buffer_size(2).build();
+        let mut buf = Vec::new();
+        let stats = process_read(b"ab\ncd\n\n".as_slice(), &mut buf, &options).unwrap();
+        assert_eq!(buf, b"ab\ncd");
+        assert_eq!(stats.chunks, 4);
+    }
+
+    #[test]
+    fn test_process_retries_interrupted() {
+        /// Fails every other call with `Interrupted`.
+        struct Flaky<T> {
+            inner: T,
+            interrupt: bool,
+        }
+
+        impl<T> Flaky<T> {
+            fn check(&mut self) -> std::io::Result<()> {
+                self.interrupt = !self.interrupt;
+                if self.interrupt {
+                    return Err(std::io::ErrorKind::Interrupted.into());
+                }
+                Ok(())
+            }
+        }
+
+        impl<R: std::io::Read> std::io::Read for Flaky<R> {
+            fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+                self.check()?;
+                self.inner.read(buf)
+            }
+        }
+
+        impl<W: std::io::Write> std::io::Write for Flaky<W> {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                self.check()?;
+                self.inner.write(buf)
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                self.inner.flush()
+            }
+        }
+
+        let input = Flaky {
+            inner: b"\na\n\nb\r\n".as_slice(),
+            interrupt: false,
+        };
+        let mut output = Flaky {
+            inner: Vec::new(),
+            interrupt: false,
+        };
+        let reader = std::io::BufReader::with_capacity(2, input);
+        let options = Options::builder().squeeze(true).build();
+        process(reader, &mut output, &options).unwrap();
+        assert_eq!(output.inner, b"\na\n\nb");
+    }
+
+    #[test]
+    fn test_read_error_keeps_content() {
+        let input = b"ab\n\ncd\r\n\re\n\n";
+        for coalesce in [0, 1024] {
+            let options = Options::builder().coalesce_writes(coalesce).build();
+            for n in 0..input.len() {
+                let reader = FailingReader {
+                    input,
+                    fail_after: n,
+                };
+                let mut output = Vec::new();
+                let err = process(BufReader::with_capacity(3, reader), &mut output, &options)
+                    .unwrap_err();
+                assert!(matches!(err, Error::Read(_)));
+                let expected = process_bytes(&input[..n], &options);
+                assert_eq!(output, *expected, "{coalesce} {n}");
+
+                let chunks = input[..n]
+                    .chunks(3)
+                    .map(Ok)
+                    .chain([Err(ErrorKind::Other.into())]);
+                let mut output = Vec::new();
+                let err = process_chunks(chunks, &mut output, &options).unwrap_err();
+                assert!(matches!(err, Error::Read(_)));
+                assert_eq!(output, *expected, "{coalesce} {n}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_write_error_after_partial_output() {
+        let input = b"ab\n\ncd\r\n\re\n\n";
+        let options = Options::default();
+        let expected = process_bytes(input, &options);
+        for n in 0..expected.len() {
+            let mut output = FaultyWriter::failing_after(n);
+            let reader = BufReader::with_capacity(3, input.as_slice());
+            let err = process(reader, &mut output, &options).unwrap_err();
+            assert!(matches!(err, Error::Write(_)));
+            assert_eq!(output.written, expected[..n]);
+
+            let mut writer = StripTrailingNewlines::new(FaultyWriter::failing_after(n));
+            assert!(input.chunks(3).any(|chunk| writer.write(chunk).is_err()));
+            assert_eq!(writer.get_ref().written, expected[..n]);
+        }
+    }
+
+    #[test]
+    fn test_short_writes() {
+        let input = b"ab\n\ncd\r\n\re\n\n";
+        for coalesce in [0, 4] {
+            let options = Options::builder().coalesce_writes(coalesce).build();
+            let expected = process_bytes(input, &options);
+            for max_write in 1..4 {
+                let mut output = FaultyWriter::short(max_write);
+                let reader = BufReader::with_capacity(3, input.as_slice());
+                process(reader, &mut output, &options).unwrap();
+                assert_eq!(output.written, *expected);
+
+                let mut writer = StripTrailingNewlines::new(FaultyWriter::short(max_write));
+                for chunk in input.chunks(3) {
+                    writer.write_all(chunk).unwrap();
+                }
+                assert_eq!(writer.finish().unwrap().written, *expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_interrupted_everywhere() {
+        let input = b"ab\n\ncd\r\n\re\n\n";
+        let options = Options::builder().flush(Flush::Line).build();
+        let expected = process_bytes(input, &options);
+        let reader = BufReader::with_capacity(3, Interrupting::new(input.as_slice()));
+        let mut output = Interrupting::new(Vec::new());
+        process(reader, &mut output, &options).unwrap();
+        assert_eq!(output.inner, *expected);
+
+        let mut output = Interrupting::new(Vec::new());
+        let chunks = input.chunks(3).map(Ok);
+        process_chunks(chunks, &mut output, &options).unwrap();
+        assert_eq!(output.inner, *expected);
+
+        let mut writer = StripTrailingNewlines::new(Interrupting::new(Vec::new()));
+        for chunk in input.chunks(3) {
+            writer.write_all(chunk).unwrap();
+        }
+        assert_eq!(writer.finish().unwrap().inner, *expected);
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_snickerdoodle_alias() {
+        let mut buf = Vec::new();
+        super::snickerdoodle(b"abc\r\n".as_slice(), &mut buf).unwrap();
+        assert_eq!(str::from_utf8(&buf).unwrap(), "abc");
+    }
+}
@@ -0,0 +1,133 @@
+//! Iterating over lines along with their terminators.
+
+use std::io::BufRead;
+use std::io::ErrorKind;
+use std::io::Result;
+
+use crate::Terminator;
+use crate::is_newline;
+
+/// An iterator over the lines of a reader that also tells how each line was
+/// terminated, which [`BufRead::lines`] throws away.
+///
+/// Lines end at `\n`, `\r\n` or a lone `\r`, as with
+/// [`strip_trailing_newlines`](crate::strip_trailing_newlines). The last line
+/// has no terminator if the input doesn't end with one. Lines are bytes, so
+/// input doesn't have to be UTF-8.
+///
+/// ```
+/// use nln_core::Lines;
+/// use nln_core::Terminator;
+///
+/// let lines: Vec<_> = Lines::new(b"a\r\nb\nc".as_slice()).collect::<Result<_, _>>()?;
+/// assert_eq!(
+///     lines,
+///     [
+///         (b"a".to_vec(), Some(Terminator::CrLf)),
+///         (b"b".to_vec(), Some(Terminator::Lf)),
+///         (b"c".to_vec(), None),
+///     ]
+/// );
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Debug)]
+pub struct Lines<R> {
+    reader: R,
+}
+
+impl<R: BufRead> Lines<R> {
+    /// Iterates over the lines of `reader`.
+    pub fn new(reader: R) -> Self {
+        Self { reader }
+    }
+
+    /// Returns the reader, positioned after the last line returned.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    /// Reads the next line into `line`, consuming its terminator and
+    /// returning which one it was.
+    fn read_line(&mut self, line: &mut Vec<u8>) -> Result<Option<Terminator>> {
+        loop {
+            let buf = match self.reader.fill_buf() {
+                Ok(buf) => buf,
+                Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+                Err(err) => return Err(err),
+            };
+            if buf.is_empty() {
+                return Ok(None);
+            }
+            let Some(i) = buf.iter().position(|&b| is_newline(b)) else {
+                line.extend_from_slice(buf);
+                let n = buf.len();
+                self.reader.consume(n);
+                continue;
+            };
+            line.extend_from_slice(&buf[..i]);
+            let cr = buf[i] == b'\r';
+            self.reader.consume(i + 1);
+            return if cr {
+                self.after_cr()
+            } else {
+                Ok(Some(Terminator::Lf))
+            };
+        }
+    }
+
+    /// Tells a `\r\n` from a lone `\r`, which may need another read.
+    fn after_cr(&mut self) -> Result<Option<Terminator>> {
+        loop {
+            match self.reader.fill_buf() {
+                Ok([b'\n', ..]) => {
+                    self.reader.consume(1);
+                    return Ok(Some(Terminator::CrLf));
+                }
+                Ok(_) => return Ok(Some(Terminator::Cr)),
+                Err(err) if err.kind() == ErrorKind::Interrupted => {}
+                Err(err) => return Err(err),
+            }
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for Lines<R> {
+    type Item = Result<(Vec<u8>, Option<Terminator>)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut line = Vec::new();
+        match self.read_line(&mut line) {
+            Ok(None) if line.is_empty() => None,
+            Ok(terminator) => Some(Ok((line, terminator))),
+            Err(err) => Some(Err(err)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::BufReader;
+
+    use super::Lines;
+    use crate::Terminator;
+
+    #[test]
+    fn test_terminators_split_across_chunks() {
+        let input = b"a\r\n\rb\r\r\n\n";
+        for capacity in 1..=input.len() {
+            let reader = BufReader::with_capacity(capacity, input.as_slice());
+            let lines: Vec<_> = Lines::new(reader).map(Result::unwrap).collect();
+            assert_eq!(
+                lines,
+                [
+                    (b"a".to_vec(), Some(Terminator::CrLf)),
+                    (b"".to_vec(), Some(Terminator::Cr)),
+                    (b"b".to_vec(), Some(Terminator::Cr)),
+                    (b"".to_vec(), Some(Terminator::CrLf)),
+                    (b"".to_vec(), Some(Terminator::Lf)),
+                ],
+                "capacity {capacity}"
+            );
+        }
+    }
+}
@@ -0,0 +1,365 @@
+//! Configuration for [`process`](crate::process).
+
+use std::fmt;
+use std::fmt::Debug;
+use std::fmt::Formatter;
+use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+
+use crate::AsciiPolicy;
+use crate::NewlinePolicy;
+
+/// A line ending to normalize terminators to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineEnding {
+    /// `\n`
+    Lf,
+    /// `\r\n`
+    CrLf,
+}
+
+impl LineEnding {
+    /// The bytes of this line ending.
+    pub fn as_bytes(self) -> &'static [u8] {
+        match self {
+            LineEnding::Lf => b"\n",
+            LineEnding::CrLf => b"\r\n",
+        }
+    }
+}
+
+/// When [`process`](crate::process) flushes its writer.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Flush {
+    /// After each chunk of input that produced output, so that input read a
+    /// line at a time comes out a line at a time.
+    Line,
+    /// Once, at the end of input.
+    #[default]
+    End,
+    /// Never; the caller flushes, such as once after many streams.
+    Never,
+}
+
+/// A transformation, for building [`Options`] as a pipeline.
+///
+/// Collecting stages into [`Options`] enables exactly those stages, starting
+/// from options that change nothing. However many stages there are, they run
+/// fused in a single scan over each buffer, and their order doesn't matter.
+///
+/// ```
+/// use nln_core::LineEnding;
+/// use nln_core::Options;
+/// use nln_core::Stage;
+///
+/// let options: Options = [
+///     Stage::StripLeading,
+///     Stage::Squeeze,
+///     Stage::Normalize(LineEnding::Lf),
+/// ]
+/// .into_iter()
+/// .collect();
+/// let out = nln_core::process_bytes(b"\r\na\r\n\r\n\r\nb\n\n", &options);
+/// assert_eq!(*out, *b"a\n\nb\n\n");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Stage {
+    /// Strips trailing terminators, keeping up to `keep` of them, like
+    /// [`OptionsBuilder::keep_trailing`].
+    StripTrailing {
+        /// How many trailing terminators to keep.
+        keep: usize,
+    },
+    /// Like [`OptionsBuilder::strip_leading`].
+    StripLeading,
+    /// Like [`OptionsBuilder::squeeze`].
+    Squeeze,
+    /// Like [`OptionsBuilder::line_ending`].
+    Normalize(LineEnding),
+}
+
+/// Settings for [`process`](crate::process).
+///
+/// The default strips all trailing newlines and leaves everything else
+/// alone, exactly like [`strip_trailing_newlines`](crate::strip_trailing_newlines).
+/// Use [`Options::builder`] to change that.
+///
+/// Terminators are `\n`, `\r\n` and a lone `\r` unless another
+/// [`NewlinePolicy`] is set with [`OptionsBuilder::policy`]. Counts such as
+/// [`OptionsBuilder::keep_trailing`] are in terminators, so `\r\n` counts once.
+#[derive(Debug, Clone)]
+pub struct Options {
+    pub(crate) strip_trailing: bool,
+    pub(crate) keep_trailing: usize,
+    pub(crate) strip_leading: bool,
+    pub(crate) squeeze: bool,
+    pub(crate) line_ending: Option<LineEnding>,
+    /// `None` for [`AsciiPolicy`], which has a faster path.
+    pub(crate) policy: Option<Arc<dyn NewlinePolicy>>,
+    pub(crate) buffer_size: usize,
+    pub(crate) coalesce: usize,
+    pub(crate) flush: Flush,
+    pub(crate) max_pending_bytes: Option<u64>,
+    pub(crate) progress: Option<Progress>,
+    pub(crate) cancel: Option<Arc<AtomicBool>>,
+}
+
+/// A callback set with [`OptionsBuilder::on_progress`].
+#[derive(Clone)]
+pub(crate) struct Progress {
+    /// Bytes of input between calls.
+    pub(crate) every: u64,
+    pub(crate) callback: Arc<dyn Fn(u64, u64) + Send + Sync>,
+}
+
+impl Debug for Progress {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Progress")
+            .field("every", &self.every)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The default of [`OptionsBuilder::buffer_size`].
+pub const DEFAULT_BUFFER_SIZE: usize = 64 * 1024;
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            strip_trailing: true,
+            keep_trailing: 0,
+            strip_leading: false,
+            squeeze: false,
+            line_ending: None,
+            policy: None,
+            buffer_size: DEFAULT_BUFFER_SIZE,
+            coalesce: 0,
+            flush: Flush::End,
+            max_pending_bytes: None,
+            progress: None,
+            cancel: None,
+        }
+    }
+}
+
+impl FromIterator<Stage> for Options {
+    fn from_iter<I: IntoIterator<Item = Stage>>(stages: I) -> Self {
+        let mut builder = Options::builder().strip_trailing(false);
+        for stage in stages {
+            builder = builder.stage(stage);
+        }
+        builder.build()
+    }
+}
+
+impl Options {
+    /// Starts building options from the defaults.
+    pub fn builder() -> OptionsBuilder {
+        OptionsBuilder(Self::default())
+    }
+
+    /// Starts building options from these.
+    pub fn to_builder(&self) -> OptionsBuilder {
+        OptionsBuilder(self.clone())
+    }
+
+    /// The size of buffers nln allocates itself, in bytes.
+    pub fn buffer_size(&self) -> usize {
+        self.buffer_size
+    }
+
+    /// The line ending newlines are rewritten as, if any.
+    pub fn line_ending(&self) -> Option<LineEnding> {
+        self.line_ending
+    }
+
+    /// Whether only the default trailing-newline stripping is configured.
+    pub(crate) fn is_plain(&self) -> bool {
+        self.policy.is_none() && !self.squeeze && self.line_ending.is_none()
+    }
+
+    /// Whether the cancel flag is set.
+    pub(crate) fn is_cancelled(&self) -> bool {
+        self.cancel
+            .as_ref()
+            .is_some_and(|cancel| cancel.load(Ordering::Relaxed))
+    }
+
+    /// Whether the output is always a prefix of the input.
+    pub(crate) fn is_prefix_only(&self) -> bool {
+        self.is_plain() && !self.strip_leading
+    }
+
+    pub(crate) fn policy(&self) -> &dyn NewlinePolicy {
+        self.policy.as_deref().unwrap_or(&AsciiPolicy)
+    }
+}
+
+/// Builder for [`Options`].
+///
+/// ```
+/// use nln_core::LineEnding;
+/// use nln_core::Options;
+///
+/// let options = Options::builder()
+///     .keep_trailing(1)
+///     .line_ending(LineEnding::Lf)
+///     .build();
+/// let mut out = Vec::new();
+/// nln_core::process(b"a\r\nb\r\n\r\n".as_slice(), &mut out, &options)?;
+/// assert_eq!(out, b"a\nb\n");
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Debug, Clone)]
+#[must_use]
+pub struct OptionsBuilder(Options);
+
+impl OptionsBuilder {
+    /// Whether to strip trailing terminators at all. Defaults to `true`.
+    pub fn strip_trailing(mut self, strip: bool) -> Self {
+        self.0.strip_trailing = strip;
+        self
+    }
+
+    /// Keeps up to `n` of the trailing terminators when stripping. Missing
+    /// ones are not added. Defaults to 0.
+    pub fn keep_trailing(mut self, n: usize) -> Self {
+        self.0.keep_trailing = n;
+        self
+    }
+
+    /// Also strips terminators before the first content. Defaults to `false`.
+    pub fn strip_leading(mut self, strip: bool) -> Self {
+        self.0.strip_leading = strip;
+        self
+    }
+
+    /// Collapses runs of blank lines into one, like `cat -s`: at most two
+    /// terminators in a row, or one before the first content. Defaults to
+    /// `false`.
+    pub fn squeeze(mut self, squeeze: bool) -> Self {
+        self.0.squeeze = squeeze;
+        self
+    }
+
+    /// Rewrites every terminator that is output as `line_ending`. Defaults to
+    /// keeping terminators as they are.
+    pub fn line_ending(mut self, line_ending: LineEnding) -> Self {
+        self.0.line_ending = Some(line_ending);
+        self
+    }
+
+    /// Sets the size of the buffers nln allocates when it does the buffering
+    /// itself, such as for files, in bytes. It doesn't change the output.
+    /// Defaults to [`DEFAULT_BUFFER_SIZE`].
+    ///
+    /// ## Panics
+    ///
+    /// Panics if `size` is 0.
+    pub fn buffer_size(mut self, size: usize) -> Self {
+        assert!(size > 0, "buffer size must be positive");
+        self.0.buffer_size = size;
+        self
+    }
+
+    /// Gathers output in a buffer until `threshold` bytes are ready instead of
+    /// writing after every chunk of input, so a producer that writes a line
+    /// at a time doesn't cost a system call per line. It doesn't change the
+    /// output, only when it is written. Defaults to 0, writing every chunk's
+    /// output right away.
+    ///
+    /// This applies to [`process`](crate::process),
+    /// [`process_read`](crate::process_read),
+    /// [`process_chunks`](crate::process_chunks) and
+    /// [`process_pipelined`](crate::process_pipelined); the writer adapters
+    /// such as [`StripTrailingNewlines`](crate::StripTrailingNewlines) leave
+    /// buffering to their inner writer.
+    pub fn coalesce_writes(mut self, threshold: usize) -> Self {
+        self.0.coalesce = threshold;
+        self
+    }
+
+    /// Sets when the writer is flushed. Defaults to [`Flush::End`].
+    ///
+    /// Output gathered by [`coalesce_writes`](Self::coalesce_writes) is
+    /// written whenever the writer is flushed, and at the end of input even
+    /// with [`Flush::Never`].
+    pub fn flush(mut self, flush: Flush) -> Self {
+        self.0.flush = flush;
+        self
+    }
+
+    /// Fails with [`Error::Limit`](crate::Error::Limit) instead of holding
+    /// back more than `max` bytes of terminators at once, bounding the memory
+    /// a hostile input can make nln use. Defaults to no limit.
+    ///
+    /// In-memory functions such as [`process_bytes`](crate::process_bytes)
+    /// ignore the limit, since their input is in memory already.
+    pub fn max_pending_bytes(mut self, max: u64) -> Self {
+        self.0.max_pending_bytes = Some(max);
+        self
+    }
+
+    /// Calls `callback` with the number of bytes read and written so far
+    /// each time another `every` bytes of input have been processed, and once
+    /// more at the end of input, so long runs can show progress. Defaults to
+    /// no callback.
+    ///
+    /// The callback runs on the processing thread, so it should return
+    /// quickly. Bytes written count output as it is produced, before the
+    /// caller writes it.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if `every` is 0.
+    pub fn on_progress(
+        mut self,
+        every: u64,
+        callback: impl Fn(u64, u64) + Send + Sync + 'static,
+    ) -> Self {
+        assert!(every > 0, "progress interval must be positive");
+        self.0.progress = Some(Progress {
+            every,
+            callback: Arc::new(callback),
+        });
+        self
+    }
+
+    /// Stops processing with [`Error::Cancelled`](crate::Error::Cancelled)
+    /// once `flag` is set, such as from another thread. Defaults to no flag.
+    ///
+    /// The flag is checked before each chunk, so a read that blocks isn't
+    /// interrupted. The output so far is written and flushed, as
+    /// [`flush`](Self::flush) allows; trailing newlines held back are not
+    /// written, since it isn't known whether they are trailing.
+    ///
+    /// In-memory functions such as [`process_bytes`](crate::process_bytes)
+    /// ignore the flag, since they never wait for input.
+    pub fn cancel_flag(mut self, flag: Arc<AtomicBool>) -> Self {
+        self.0.cancel = Some(flag);
+        self
+    }
+
+    /// Decides what counts as a terminator. Defaults to [`AsciiPolicy`].
+    pub fn policy(mut self, policy: impl NewlinePolicy + 'static) -> Self {
+        self.0.policy = Some(Arc::new(policy));
+        self
+    }
+
+    /// Enables `stage` on top of what is configured already.
+    pub fn stage(self, stage: Stage) -> Self {
+        match stage {
+            Stage::StripTrailing { keep } => self.strip_trailing(true).keep_trailing(keep),
+            Stage::StripLeading => self.strip_leading(true),
+            Stage::Squeeze => self.squeeze(true),
+            Stage::Normalize(line_ending) => self.line_ending(line_ending),
+        }
+    }
+
+    /// Finishes building.
+    pub fn build(self) -> Options {
+        self.0
+    }
+}
@@ -0,0 +1,253 @@
+//! What counts as a line terminator.
+
+use std::fmt;
+
+/// The longest terminator a [`NewlinePolicy`] may match, in bytes.
+pub const MAX_TERMINATOR_LEN: usize = 4;
+
+/// Whether `b` is `\n` or `\r`, the bytes that
+/// [`strip_trailing_newlines`](crate::strip_trailing_newlines) strips.
+pub const fn is_newline(b: u8) -> bool {
+    b == b'\r' || b == b'\n'
+}
+
+/// A line terminator, as matched by [`UnicodePolicy`].
+///
+/// ```
+/// use nln_core::Terminator;
+///
+/// let text = b"a\r\nb\xE2\x80\xA8";
+/// assert_eq!(Terminator::at_start(&text[1..]), Some(Terminator::CrLf));
+/// assert_eq!(Terminator::at_start(&text[2..]), Some(Terminator::Lf));
+/// assert_eq!(Terminator::at_start(text), None);
+/// let last = Terminator::at_end(text).unwrap();
+/// assert_eq!(last, Terminator::LineSeparator);
+/// assert_eq!(last.as_bytes().len(), 3);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Terminator {
+    /// `\n`
+    Lf,
+    /// `\r\n`
+    CrLf,
+    /// `\r` not followed by `\n`
+    Cr,
+    /// NEXT LINE, U+0085
+    Nel,
+    /// LINE SEPARATOR, U+2028
+    LineSeparator,
+    /// PARAGRAPH SEPARATOR, U+2029
+    ParagraphSeparator,
+}
+
+impl Terminator {
+    /// The bytes of this terminator, in UTF-8.
+    pub const fn as_bytes(self) -> &'static [u8] {
+        match self {
+            Terminator::Lf => b"\n",
+            Terminator::CrLf => b"\r\n",
+            Terminator::Cr => b"\r",
+            Terminator::Nel => "\u{85}".as_bytes(),
+            Terminator::LineSeparator => "\u{2028}".as_bytes(),
+            Terminator::ParagraphSeparator => "\u{2029}".as_bytes(),
+        }
+    }
+
+    /// Whether this is `\n`, `\r\n` or `\r`, the terminators of
+    /// [`AsciiPolicy`].
+    pub const fn is_ascii(self) -> bool {
+        matches!(self, Terminator::Lf | Terminator::CrLf | Terminator::Cr)
+    }
+
+    /// The terminator that `bytes` starts with, taking a `\r\n` as one.
+    pub const fn at_start(bytes: &[u8]) -> Option<Self> {
+        Some(match bytes {
+            [b'\r', b'\n', ..] => Terminator::CrLf,
+            [b'\n', ..] => Terminator::Lf,
+            [b'\r', ..] => Terminator::Cr,
+            [0xC2, 0x85, ..] => Terminator::Nel,
+            [0xE2, 0x80, 0xA8, ..] => Terminator::LineSeparator,
+            [0xE2, 0x80, 0xA9, ..] => Terminator::ParagraphSeparator,
+            _ => return None,
+        })
+    }
+
+    /// The terminator that `bytes` ends with, taking a `\r\n` as one.
+    pub const fn at_end(bytes: &[u8]) -> Option<Self> {
+        Some(match bytes {
+            [.., b'\r', b'\n'] => Terminator::CrLf,
+            [.., b'\n'] => Terminator::Lf,
+            [.., b'\r'] => Terminator::Cr,
+            [.., 0xC2, 0x85] => Terminator::Nel,
+            [.., 0xE2, 0x80, 0xA8] => Terminator::LineSeparator,
+            [.., 0xE2, 0x80, 0xA9] => Terminator::ParagraphSeparator,
+            _ => return None,
+        })
+    }
+}
+
+/// What the bytes at some position of the input start with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TerminatorMatch {
+    /// Content, at least for the first byte.
+    Content,
+    /// A terminator of this many bytes.
+    Terminator(usize),
+    /// A prefix of a terminator that needs more input to decide. Only
+    /// allowed when given fewer than [`MAX_TERMINATOR_LEN`] bytes; at the
+    /// end of input the bytes are treated as content.
+    Incomplete,
+}
+
+/// Decides what counts as a line terminator.
+///
+/// A `\r` terminator directly followed by a `\n` terminator is counted as one
+/// `\r\n`, also when the two are split across chunks.
+///
+/// ```
+/// use nln_core::LfPolicy;
+/// use nln_core::Options;
+///
+/// // a lone `\r` is content when only `\n` terminates lines
+/// let options = Options::builder().policy(LfPolicy).build();
+/// assert_eq!(nln_core::process_bytes(b"a\r\n\r", &options), b"a\r\n\r".as_slice());
+/// ```
+pub trait NewlinePolicy: fmt::Debug + Send + Sync {
+    /// Classifies the start of `bytes`, which is never empty.
+    fn classify(&self, bytes: &[u8]) -> TerminatorMatch;
+
+    /// Whether `b` may be the first byte of a terminator. Returning `true`
+    /// for other bytes is allowed, but slower.
+    fn may_start(&self, b: u8) -> bool;
+
+    /// All bytes that may start a terminator, if there are at most four, so
+    /// candidates can be found with a vectorized search instead of calling
+    /// [`may_start`](Self::may_start) for every byte. Defaults to `None`.
+    fn start_bytes(&self) -> Option<&[u8]> {
+        None
+    }
+}
+
+/// `\n`, `\r\n` and a lone `\r`. This is the default.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AsciiPolicy;
+
+impl NewlinePolicy for AsciiPolicy {
+    fn classify(&self, bytes: &[u8]) -> TerminatorMatch {
+        match bytes {
+            [b'\r', b'\n', ..] => TerminatorMatch::Terminator(2),
+            [b'\n' | b'\r', ..] => TerminatorMatch::Terminator(1),
+            _ => TerminatorMatch::Content,
+        }
+    }
+
+    fn may_start(&self, b: u8) -> bool {
+        b == b'\n' || b == b'\r'
+    }
+
+    fn start_bytes(&self) -> Option<&[u8]> {
+        Some(b"\n\r")
+    }
+}
+
+/// `\n` only, so a `\r` before it is content.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LfPolicy;
+
+impl NewlinePolicy for LfPolicy {
+    fn classify(&self, bytes: &[u8]) -> TerminatorMatch {
+        match bytes {
+            [b'\n', ..] => TerminatorMatch::Terminator(1),
+            _ => TerminatorMatch::Content,
+        }
+    }
+
+    fn may_start(&self, b: u8) -> bool {
+        b == b'\n'
+    }
+
+    fn start_bytes(&self) -> Option<&[u8]> {
+        Some(b"\n")
+    }
+}
+
+/// `\r\n` only, so a lone `\n` or `\r` is content.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CrLfPolicy;
+
+impl NewlinePolicy for CrLfPolicy {
+    fn classify(&self, bytes: &[u8]) -> TerminatorMatch {
+        match bytes {
+            [b'\r', b'\n', ..] => TerminatorMatch::Terminator(2),
+            [b'\r'] => TerminatorMatch::Incomplete,
+            _ => TerminatorMatch::Content,
+        }
+    }
+
+    fn may_start(&self, b: u8) -> bool {
+        b == b'\r'
+    }
+
+    fn start_bytes(&self) -> Option<&[u8]> {
+        Some(b"\r")
+    }
+}
+
+/// Everything [`AsciiPolicy`] matches, plus NEL (U+0085), LINE SEPARATOR
+/// (U+2028) and PARAGRAPH SEPARATOR (U+2029) encoded as UTF-8.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct UnicodePolicy;
+
+impl NewlinePolicy for UnicodePolicy {
+    fn classify(&self, bytes: &[u8]) -> TerminatorMatch {
+        match bytes {
+            [0xC2, 0x85, ..] => TerminatorMatch::Terminator(2),
+            [0xE2, 0x80, 0xA8 | 0xA9, ..] => TerminatorMatch::Terminator(3),
+            [0xC2] | [0xE2] | [0xE2, 0x80] => TerminatorMatch::Incomplete,
+            _ => AsciiPolicy.classify(bytes),
+        }
+    }
+
+    fn may_start(&self, b: u8) -> bool {
+        AsciiPolicy.may_start(b) || b == 0xC2 || b == 0xE2
+    }
+
+    fn start_bytes(&self) -> Option<&[u8]> {
+        Some(b"\n\r\xC2\xE2")
+    }
+}
+
+/// Single bytes for which a predicate returns `true`.
+///
+/// ```
+/// use nln_core::Options;
+/// use nln_core::PredicatePolicy;
+///
+/// // also strip trailing NUL bytes
+/// let policy = PredicatePolicy(|b| matches!(b, b'\n' | b'\r' | 0));
+/// let options = Options::builder().policy(policy).build();
+/// assert_eq!(nln_core::process_bytes(b"a\n\0\0", &options), b"a".as_slice());
+/// ```
+#[derive(Clone, Copy)]
+pub struct PredicatePolicy<F>(pub F);
+
+impl<F> fmt::Debug for PredicatePolicy<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("PredicatePolicy")
+    }
+}
+
+impl<F: Fn(u8) -> bool + Send + Sync> NewlinePolicy for PredicatePolicy<F> {
+    fn classify(&self, bytes: &[u8]) -> TerminatorMatch {
+        if (self.0)(bytes[0]) {
+            TerminatorMatch::Terminator(1)
+        } else {
+            TerminatorMatch::Content
+        }
+    }
+
+    fn may_start(&self, b: u8) -> bool {
+        (self.0)(b)
+    }
+}
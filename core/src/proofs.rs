@@ -0,0 +1,61 @@
+//! Proofs for [Kani](https://model-checking.github.io/kani/), which checks
+//! them for every input up to a few bytes long. Run them with `mise run
+//! verify`.
+//!
+//! Kani doesn't model the vectorized searches, so these build with the plain
+//! loops in their place.
+
+use crate::Options;
+use crate::Stripper;
+use crate::is_newline;
+use crate::strip_trailing_newlines_bytes;
+
+/// The longest input checked. Every extra byte multiplies the time Kani
+/// takes, and a handful already covers a `\r\n` split across chunks.
+const MAX_LEN: usize = 5;
+
+/// An input of any bytes, up to [`MAX_LEN`] of them.
+fn any_input(buf: &mut [u8; MAX_LEN]) -> &[u8] {
+    *buf = kani::any();
+    let len: usize = kani::any();
+    kani::assume(len <= MAX_LEN);
+    &buf[..len]
+}
+
+/// That `output` is `input` with its trailing newlines removed: a prefix of
+/// it that doesn't end in a newline byte, followed in `input` by nothing but
+/// newline bytes.
+fn assert_stripped(input: &[u8], output: &[u8]) {
+    assert!(input.starts_with(output));
+    assert!(output.last().is_none_or(|&b| !is_newline(b)));
+    assert!(input[output.len()..].iter().all(|&b| is_newline(b)));
+}
+
+#[kani::proof]
+#[kani::unwind(7)]
+fn strip_bytes() {
+    let mut buf = [0; MAX_LEN];
+    let input = any_input(&mut buf);
+    assert_stripped(input, strip_trailing_newlines_bytes(input));
+}
+
+#[kani::proof]
+#[kani::unwind(7)]
+fn stripper_in_two_chunks() {
+    let mut buf = [0; MAX_LEN];
+    let input = any_input(&mut buf);
+    let split: usize = kani::any();
+    kani::assume(split <= input.len());
+
+    let mut stripper = Stripper::new(Options::default());
+    let mut output = Vec::with_capacity(MAX_LEN);
+    for chunk in [&input[..split], &input[split..]] {
+        for piece in stripper.push(chunk).unwrap() {
+            output.extend_from_slice(piece);
+        }
+    }
+    for piece in stripper.finish() {
+        output.extend_from_slice(piece);
+    }
+    assert_stripped(input, &output);
+}
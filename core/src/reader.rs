@@ -0,0 +1,141 @@
+//! A [`BufRead`] adapter over [`Stripper`].
+
+use std::io::BufRead;
+use std::io::ErrorKind;
+use std::io::Read;
+use std::io::Result;
+
+use crate::Options;
+use crate::Stats;
+use crate::Stripper;
+
+/// Wraps a reader, stripping trailing newlines from what is read through it.
+///
+/// Input is processed as it is read, so the output never ends in newlines
+/// without the whole input being buffered first.
+///
+/// ```
+/// use std::io::Read;
+///
+/// let mut reader = nln_core::StrippedReader::new(b"hello\r\n\r\n".as_slice());
+/// let mut out = String::new();
+/// reader.read_to_string(&mut out)?;
+/// assert_eq!(out, "hello");
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Debug)]
+pub struct StrippedReader<R: BufRead> {
+    inner: R,
+    stripper: Stripper,
+    /// Output not yet read.
+    buf: Vec<u8>,
+    pos: usize,
+    finished: bool,
+}
+
+impl<R: BufRead> StrippedReader<R> {
+    /// Wraps `inner` with the default options.
+    pub fn new(inner: R) -> Self {
+        Self::with_options(inner, Options::default())
+    }
+
+    /// Wraps `inner`, processing what is read as configured by `options`.
+    pub fn with_options(inner: R, options: Options) -> Self {
+        Self {
+            inner,
+            stripper: Stripper::new(options),
+            buf: Vec::new(),
+            pos: 0,
+            finished: false,
+        }
+    }
+
+    /// Statistics about what has been read from the inner reader so far.
+    pub fn stats(&self) -> Stats {
+        self.stripper.stats()
+    }
+
+    /// The inner reader.
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Returns the inner reader, discarding any output not yet read.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: BufRead> BufRead for StrippedReader<R> {
+    fn fill_buf(&mut self) -> Result<&[u8]> {
+        // a chunk of input can produce no output, so keep going until it does
+        while self.pos == self.buf.len() && !self.finished {
+            self.buf.clear();
+            self.pos = 0;
+            let chunk = match self.inner.fill_buf() {
+                Ok(chunk) => chunk,
+                Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+                Err(err) => return Err(err),
+            };
+            let n = chunk.len();
+            if n == 0 {
+                event!(debug, stats = ?self.stripper.stats(), "finished reading");
+                self.finished = true;
+                self.stripper
+                    .finish()
+                    .for_each(|piece| self.buf.extend_from_slice(piece));
+            } else {
+                event!(trace, bytes = n, "read chunk");
+                self.stripper
+                    .push(chunk)?
+                    .for_each(|piece| self.buf.extend_from_slice(piece));
+                self.inner.consume(n);
+            }
+        }
+        Ok(&self.buf[self.pos..])
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.pos = (self.pos + amt).min(self.buf.len());
+    }
+}
+
+impl<R: BufRead> Read for StrippedReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        let available = self.fill_buf()?;
+        let n = available.len().min(buf.len());
+        buf[..n].copy_from_slice(&available[..n]);
+        self.consume(n);
+        Ok(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::BufRead;
+    use std::io::BufReader;
+    use std::io::Read;
+
+    use super::StrippedReader;
+    use crate::Options;
+
+    #[test]
+    fn test_small_reads() {
+        let input = b"\na\r\n\nb\r\n\n\r".as_slice();
+        let mut reader = StrippedReader::new(BufReader::with_capacity(2, input));
+        let mut out = Vec::new();
+        let mut byte = [0];
+        while reader.read(&mut byte).unwrap() == 1 {
+            out.push(byte[0]);
+        }
+        assert_eq!(out, b"\na\r\n\nb");
+    }
+
+    #[test]
+    fn test_lines_keep_trailing() {
+        let options = Options::builder().keep_trailing(1).build();
+        let reader = StrippedReader::with_options(b"a\nb\n\n\n".as_slice(), options);
+        let lines: Vec<String> = reader.lines().map(Result::unwrap).collect();
+        assert_eq!(lines, ["a", "b"]);
+    }
+}
@@ -0,0 +1,87 @@
+//! Searching for any of up to four bytes at once.
+//!
+//! memchr stops at three needles, which leaves out
+//! [`UnicodePolicy`](crate::UnicodePolicy) with its four start bytes. SSE2 is
+//! part of every x86-64 CPU, so the vectorized search needs no runtime
+//! detection there; other targets use a plain loop.
+
+/// The most needles [`find_any`] searches for at once.
+pub(crate) const MAX_NEEDLES: usize = 4;
+
+/// Finds the first byte of `haystack` that is one of `needles`, of which
+/// there are between one and [`MAX_NEEDLES`].
+pub(crate) fn find_any(needles: &[u8], haystack: &[u8]) -> Option<usize> {
+    debug_assert!((1..=MAX_NEEDLES).contains(&needles.len()));
+    // repeating a needle doesn't change what is found
+    let needles = [0, 1, 2, 3].map(|i| needles[i.min(needles.len() - 1)]);
+    imp::find_any(needles, haystack)
+}
+
+fn find_scalar(needles: [u8; MAX_NEEDLES], haystack: &[u8]) -> Option<usize> {
+    haystack.iter().position(|b| needles.contains(b))
+}
+
+#[cfg(all(target_arch = "x86_64", not(kani)))]
+mod imp {
+    use std::arch::x86_64::__m128i;
+    use std::arch::x86_64::_mm_cmpeq_epi8;
+    use std::arch::x86_64::_mm_loadu_si128;
+    use std::arch::x86_64::_mm_movemask_epi8;
+    use std::arch::x86_64::_mm_or_si128;
+    use std::arch::x86_64::_mm_set1_epi8;
+
+    use super::MAX_NEEDLES;
+    use super::find_scalar;
+
+    const LANES: usize = size_of::<__m128i>();
+
+    pub(super) fn find_any(needles: [u8; MAX_NEEDLES], haystack: &[u8]) -> Option<usize> {
+        // SAFETY: every x86-64 CPU has SSE2
+        unsafe { find_sse2(needles, haystack) }
+    }
+
+    #[target_feature(enable = "sse2")]
+    fn find_sse2(needles: [u8; MAX_NEEDLES], haystack: &[u8]) -> Option<usize> {
+        let [a, b, c, d] = needles.map(|n| _mm_set1_epi8(n.cast_signed()));
+        let mut chunks = haystack.chunks_exact(LANES);
+        for (i, chunk) in chunks.by_ref().enumerate() {
+            // SAFETY: the chunk is `LANES` bytes long, and the load doesn't
+            // need to be aligned
+            let v = unsafe { _mm_loadu_si128(chunk.as_ptr().cast()) };
+            let found = _mm_or_si128(
+                _mm_or_si128(_mm_cmpeq_epi8(v, a), _mm_cmpeq_epi8(v, b)),
+                _mm_or_si128(_mm_cmpeq_epi8(v, c), _mm_cmpeq_epi8(v, d)),
+            );
+            let mask = _mm_movemask_epi8(found);
+            if mask != 0 {
+                return Some(i * LANES + mask.trailing_zeros() as usize);
+            }
+        }
+        let rest = chunks.remainder();
+        find_scalar(needles, rest).map(|i| haystack.len() - rest.len() + i)
+    }
+}
+
+#[cfg(any(not(target_arch = "x86_64"), kani))]
+mod imp {
+    pub(super) use super::find_scalar as find_any;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::find_any;
+
+    #[test]
+    fn test_find_any_matches_scalar() {
+        let haystack: Vec<u8> = (0..200u32).map(|i| (i * 37 % 251) as u8).collect();
+        for needles in [&b"\n"[..], b"\n\r", b"\n\r\xC2", b"\n\r\xC2\xE2", b"\0\xFF"] {
+            for start in 0..40 {
+                for end in start..haystack.len() {
+                    let haystack = &haystack[start..end];
+                    let expected = haystack.iter().position(|b| needles.contains(b));
+                    assert_eq!(find_any(needles, haystack), expected, "{needles:?}");
+                }
+            }
+        }
+    }
+}
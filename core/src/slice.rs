@@ -0,0 +1,199 @@
+//! In-memory counterparts of the streaming functions.
+
+use std::borrow::Cow;
+
+use crate::Options;
+use crate::Stripper;
+use crate::is_newline;
+
+/// Returns `bytes` without its trailing newlines, like
+/// [`strip_trailing_newlines`](crate::strip_trailing_newlines).
+///
+/// ```
+/// assert_eq!(nln_core::strip_trailing_newlines_bytes(b"a\n\r\n"), b"a");
+/// ```
+pub const fn strip_trailing_newlines_bytes(bytes: &[u8]) -> &[u8] {
+    let mut end = bytes.len();
+    while end > 0 && is_newline(bytes[end - 1]) {
+        end -= 1;
+    }
+    bytes.split_at(end).0
+}
+
+/// Returns `s` without its trailing newlines, like
+/// [`strip_trailing_newlines`](crate::strip_trailing_newlines).
+///
+/// This is a `const fn`, so it can strip constants at compile time:
+///
+/// ```
+/// const GREETING: &str = nln_core::strip_trailing_newlines_str("hello\n\n");
+/// assert_eq!(GREETING, "hello");
+/// ```
+pub const fn strip_trailing_newlines_str(s: &str) -> &str {
+    // only ASCII bytes are removed, so this is a char boundary
+    s.split_at(strip_trailing_newlines_bytes(s.as_bytes()).len())
+        .0
+}
+
+/// Processes `bytes` as configured by `options`, like
+/// [`process`](crate::process).
+///
+/// Borrows from `bytes` when the output is a prefix of it, which is always
+/// the case when only trailing newlines are stripped, and allocates
+/// otherwise.
+///
+/// The input is in memory already, so the
+/// [`max_pending_bytes`](crate::OptionsBuilder::max_pending_bytes) limit and
+/// the [`cancel_flag`](crate::OptionsBuilder::cancel_flag) are ignored.
+///
+/// ```
+/// use std::borrow::Cow;
+///
+/// let options = nln_core::Options::builder().squeeze(true).build();
+/// assert!(matches!(nln_core::process_bytes(b"a\n\nb\n", &options), Cow::Borrowed(b"a\n\nb")));
+/// assert!(matches!(nln_core::process_bytes(b"a\n\n\nb\n", &options), Cow::Owned(_)));
+/// ```
+pub fn process_bytes<'a>(bytes: &'a [u8], options: &Options) -> Cow<'a, [u8]> {
+    let mut options = options.clone();
+    options.max_pending_bytes = None;
+    options.cancel = None;
+    let mut stripper = Stripper::new(options);
+    let mut output = Output::Prefix(0);
+    let pieces = stripper
+        .push(bytes)
+        .expect("there is no limit or cancel flag");
+    for piece in pieces {
+        output.extend(bytes, piece);
+    }
+    for piece in stripper.finish() {
+        output.extend(bytes, piece);
+    }
+    match output {
+        Output::Prefix(len) => Cow::Borrowed(&bytes[..len]),
+        Output::Owned(owned) => Cow::Owned(owned),
+    }
+}
+
+/// Processes `s` as configured by `options`, like [`process`](crate::process).
+///
+/// Borrows from `s` when the output is a prefix of it and allocates
+/// otherwise, as [`process_bytes`] does.
+///
+/// The built-in policies only match whole characters, so the output is
+/// always UTF-8 with them. A [`PredicatePolicy`](crate::PredicatePolicy) can
+/// match bytes inside a character, and what is left of such a character is
+/// replaced with U+FFFD REPLACEMENT CHARACTER.
+pub fn process_str<'a>(s: &'a str, options: &Options) -> Cow<'a, str> {
+    match process_bytes(s.as_bytes(), options) {
+        Cow::Borrowed(bytes) if s.is_char_boundary(bytes.len()) => Cow::Borrowed(&s[..bytes.len()]),
+        Cow::Borrowed(bytes) => Cow::Owned(String::from_utf8_lossy(bytes).into_owned()),
+        Cow::Owned(bytes) => Cow::Owned(
+            String::from_utf8(bytes)
+                .unwrap_or_else(|err| String::from_utf8_lossy(err.as_bytes()).into_owned()),
+        ),
+    }
+}
+
+/// Output collected so far, kept as a length while it matches the input.
+enum Output {
+    Prefix(usize),
+    Owned(Vec<u8>),
+}
+
+impl Output {
+    fn extend(&mut self, input: &[u8], piece: &[u8]) {
+        match self {
+            Output::Prefix(len) if input[*len..].starts_with(piece) => *len += piece.len(),
+            Output::Prefix(len) => {
+                let mut owned = input[..*len].to_vec();
+                owned.extend_from_slice(piece);
+                *self = Output::Owned(owned);
+            }
+            Output::Owned(owned) => owned.extend_from_slice(piece),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+    use std::sync::Arc;
+    use std::sync::atomic::AtomicBool;
+
+    use super::process_bytes;
+    use super::process_str;
+    use super::strip_trailing_newlines_bytes;
+    use super::strip_trailing_newlines_str;
+    use crate::LineEnding;
+    use crate::Options;
+    use crate::PredicatePolicy;
+    use crate::strip_trailing_newlines;
+
+    const INPUTS: [&str; 10] = [
+        "",
+        "abc",
+        "\n\n\n",
+        "\r\r\r",
+        "abc\r\n\n\r",
+        "\n\nabc\n\n",
+        "\r\nabc",
+        "a\rb\nc\r\nd\n",
+        "ab\n\nc\n",
+        "é\u{2028}\n",
+    ];
+
+    #[test]
+    fn test_matches_streaming() {
+        for input in INPUTS {
+            let mut expected = Vec::new();
+            strip_trailing_newlines(input.as_bytes(), &mut expected).unwrap();
+            assert_eq!(strip_trailing_newlines_bytes(input.as_bytes()), expected);
+            assert_eq!(strip_trailing_newlines_str(input).as_bytes(), expected);
+            let options = Options::default();
+            assert!(matches!(
+                process_bytes(input.as_bytes(), &options),
+                Cow::Borrowed(bytes) if bytes == expected
+            ));
+            assert!(matches!(
+                process_str(input, &options),
+                Cow::Borrowed(s) if s.as_bytes() == expected
+            ));
+        }
+    }
+
+    #[test]
+    fn test_owned_when_changed() {
+        let options = Options::builder().line_ending(LineEnding::CrLf).build();
+        assert_eq!(process_str("a\nb\n", &options), "a\r\nb");
+        assert!(matches!(
+            process_str("a\r\nb\n", &options),
+            Cow::Borrowed("a\r\nb")
+        ));
+
+        let options = Options::builder().strip_leading(true).build();
+        assert!(matches!(process_bytes(b"\na", &options), Cow::Owned(owned) if owned == b"a"));
+    }
+
+    #[test]
+    fn test_ignores_limit_and_cancel_flag() {
+        let options = Options::builder()
+            .max_pending_bytes(1)
+            .cancel_flag(Arc::new(AtomicBool::new(true)))
+            .build();
+        assert_eq!(process_bytes(b"a\n\n\nb\n\n", &options), &b"a\n\n\nb"[..]);
+        assert_eq!(process_str("a\n\n", &options), "a");
+    }
+
+    #[test]
+    fn test_str_with_bytes_inside_characters() {
+        // 0xA9 ends the two bytes of ©
+        let policy = PredicatePolicy(|b| b == b'\n' || b == 0xA9);
+        let options = Options::builder().policy(policy).build();
+        assert_eq!(process_str("a\u{a9}", &options), "a\u{fffd}");
+        let options = options.to_builder().line_ending(LineEnding::CrLf).build();
+        assert_eq!(
+            process_str("\u{a9}\na\u{a9}", &options),
+            "\u{fffd}\r\n\r\na\u{fffd}"
+        );
+    }
+}
@@ -0,0 +1,28 @@
+//! Statistics returned by [`process`](crate::process).
+
+/// What happened while processing a stream.
+///
+/// ```
+/// let mut out = Vec::new();
+/// let stats = nln_core::process(b"a\r\n\n".as_slice(), &mut out, &nln_core::Options::default())?;
+/// assert_eq!((stats.bytes_in, stats.bytes_out), (4, 1));
+/// assert_eq!((stats.lf_stripped, stats.cr_stripped), (2, 1));
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Stats {
+    /// Bytes read from the input.
+    pub bytes_in: u64,
+    /// Bytes written to the output.
+    pub bytes_out: u64,
+    /// `\n` bytes of terminators that were removed.
+    pub lf_stripped: u64,
+    /// `\r` bytes of terminators that were removed.
+    pub cr_stripped: u64,
+    /// Terminators written as a different line ending, as configured by
+    /// [`OptionsBuilder::line_ending`](crate::OptionsBuilder::line_ending).
+    pub terminators_rewritten: u64,
+    /// Number of chunks the input was read in.
+    pub chunks: u64,
+}
@@ -0,0 +1,146 @@
+//! A [`Stream`] adapter over [`BytesStripper`].
+
+use std::collections::VecDeque;
+use std::io;
+use std::pin::Pin;
+use std::task::Context;
+use std::task::Poll;
+use std::task::ready;
+
+use bytes::Bytes;
+use futures_core::Stream;
+
+use crate::BytesStripper;
+use crate::Options;
+use crate::Stats;
+
+/// Wraps a stream of [`Bytes`], such as an HTTP body, stripping trailing
+/// newlines from it.
+///
+/// Frames of output are slices of the input frames where possible, like with
+/// [`BytesStripper`]. The trailing terminators are dropped when the inner
+/// stream ends. An error from the inner stream is passed on, and the stream
+/// can be polled again after it.
+///
+/// The inner stream must be [`Unpin`]; pin it with [`Box::pin`] if it isn't.
+#[derive(Debug)]
+pub struct StripTrailingNewlinesStream<S> {
+    inner: S,
+    stripper: BytesStripper,
+    /// Output not yet yielded.
+    queue: VecDeque<Bytes>,
+    done: bool,
+}
+
+impl<S> StripTrailingNewlinesStream<S> {
+    /// Wraps `inner` with the default options.
+    pub fn new(inner: S) -> Self {
+        Self::with_options(inner, Options::default())
+    }
+
+    /// Wraps `inner`, processing its frames as configured by `options`.
+    pub fn with_options(inner: S, options: Options) -> Self {
+        Self {
+            inner,
+            stripper: BytesStripper::new(options),
+            queue: VecDeque::new(),
+            done: false,
+        }
+    }
+
+    /// Statistics about the stream so far.
+    pub fn stats(&self) -> Stats {
+        self.stripper.stats()
+    }
+
+    /// The inner stream.
+    pub fn get_ref(&self) -> &S {
+        &self.inner
+    }
+
+    /// Returns the inner stream, dropping anything not yet yielded.
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+}
+
+impl<S: Stream<Item = io::Result<Bytes>> + Unpin> Stream for StripTrailingNewlinesStream<S> {
+    type Item = io::Result<Bytes>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        loop {
+            if let Some(frame) = this.queue.pop_front() {
+                return Poll::Ready(Some(Ok(frame)));
+            }
+            if this.done {
+                return Poll::Ready(None);
+            }
+            match ready!(Pin::new(&mut this.inner).poll_next(cx)) {
+                Some(Ok(frame)) => match this.stripper.push(&frame) {
+                    Ok(pieces) => this.queue.extend(pieces),
+                    Err(err) => {
+                        this.done = true;
+                        return Poll::Ready(Some(Err(err.into())));
+                    }
+                },
+                Some(Err(err)) => return Poll::Ready(Some(Err(err))),
+                None => {
+                    this.done = true;
+                    this.queue.extend(this.stripper.finish());
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+    use std::pin::Pin;
+    use std::task::Context;
+    use std::task::Poll;
+    use std::task::Waker;
+    use std::vec;
+
+    use bytes::Bytes;
+    use futures_core::Stream;
+
+    use crate::Options;
+    use crate::StripTrailingNewlinesStream;
+
+    /// A stream that is always ready.
+    struct Ready(vec::IntoIter<io::Result<Bytes>>);
+
+    impl Stream for Ready {
+        type Item = io::Result<Bytes>;
+
+        fn poll_next(mut self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+            Poll::Ready(self.0.next())
+        }
+    }
+
+    #[test]
+    fn test_stream() {
+        let frames = vec![
+            Ok(Bytes::from("a\r")),
+            Err(io::ErrorKind::Other.into()),
+            Ok(Bytes::from("\n\nb\n")),
+            Ok(Bytes::from("\n")),
+        ];
+        let options = Options::builder().keep_trailing(1).build();
+        let mut stream =
+            StripTrailingNewlinesStream::with_options(Ready(frames.into_iter()), options);
+        let mut out = Vec::new();
+        let mut errors = 0;
+        let mut cx = Context::from_waker(Waker::noop());
+        while let Poll::Ready(Some(frame)) = Pin::new(&mut stream).poll_next(&mut cx) {
+            match frame {
+                Ok(frame) => out.extend_from_slice(&frame),
+                Err(_) => errors += 1,
+            }
+        }
+        assert_eq!(out, b"a\r\n\nb\n");
+        assert_eq!(errors, 1);
+    }
+}
@@ -0,0 +1,704 @@
+//! The sans-IO state machine behind [`process`](crate::process).
+
+use std::iter;
+use std::ops::Range;
+
+use crate::AsciiPolicy;
+use crate::Error;
+use crate::MAX_TERMINATOR_LEN;
+use crate::NewlinePolicy;
+use crate::Options;
+use crate::Stats;
+use crate::TerminatorMatch;
+use crate::is_newline;
+use crate::simd;
+
+/// A line terminator, as matched by the policy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Terminator {
+    bytes: [u8; MAX_TERMINATOR_LEN],
+    len: u8,
+}
+
+impl Terminator {
+    const CRLF: Terminator = Terminator {
+        bytes: [b'\r', b'\n', 0, 0],
+        len: 2,
+    };
+
+    fn new(bytes: &[u8]) -> Self {
+        assert!(
+            (1..=MAX_TERMINATOR_LEN).contains(&bytes.len()),
+            "newline policy matched a terminator of {} bytes",
+            bytes.len()
+        );
+        let mut terminator = Terminator {
+            bytes: [0; MAX_TERMINATOR_LEN],
+            len: bytes.len() as u8,
+        };
+        terminator.bytes[..bytes.len()].copy_from_slice(bytes);
+        terminator
+    }
+
+    fn as_bytes(&self) -> &[u8] {
+        &self.bytes[..usize::from(self.len)]
+    }
+}
+
+/// Strips trailing newlines from a stream without doing any I/O itself.
+///
+/// Feed the input to [`push`](Self::push) in chunks of any size, writing out
+/// the pieces it returns, then call [`finish`](Self::finish) at the end of
+/// input and write out those too. This is what [`process`](crate::process) is
+/// built on, for callers that bring their own I/O, such as async runtimes or
+/// FFI layers.
+///
+/// ```
+/// let mut stripper = nln_core::Stripper::new(nln_core::Options::default());
+/// let mut out = Vec::new();
+/// for chunk in [b"hello\n".as_slice(), b"\nworld\r", b"\n\n"] {
+///     stripper.push(chunk)?.for_each(|piece| out.extend_from_slice(piece));
+/// }
+/// stripper.finish().for_each(|piece| out.extend_from_slice(piece));
+/// assert_eq!(out, b"hello\n\nworld");
+/// # Ok::<(), nln_core::Error>(())
+/// ```
+///
+/// Terminators are held back until content follows them, since only then is
+/// it known that they aren't trailing. They are kept as runs of identical
+/// terminators, so memory use is proportional to the number of alternations
+/// between kinds of terminators in a row rather than to their bytes. The
+/// buffers behind the returned pieces are reused from chunk to chunk, so once
+/// they have grown to fit the input, pushing more doesn't allocate. Pushing
+/// more input after `finish` is not supported.
+#[derive(Debug, Clone)]
+pub struct Stripper {
+    options: Options,
+    /// The terminators since the last content.
+    pending: Vec<Run>,
+    /// The number of bytes in `pending`.
+    pending_bytes: u64,
+    /// Start of a multi-byte terminator at the end of the previous chunk.
+    carry: Vec<u8>,
+    /// Whether any content has been output.
+    started: bool,
+    /// Output that doesn't come straight from the pushed chunk.
+    buffer: Vec<u8>,
+    /// The output of the current call, in order.
+    segments: Vec<Segment>,
+    stats: Stats,
+    /// Input bytes at which to call the progress callback next.
+    next_progress: u64,
+}
+
+/// Identical terminators in a row.
+#[derive(Debug, Clone, Copy)]
+struct Run {
+    terminator: Terminator,
+    count: usize,
+}
+
+/// A piece of output, as a range of either the pushed chunk or the buffer.
+#[derive(Debug, Clone)]
+enum Segment {
+    Chunk(Range<usize>),
+    Buffer(Range<usize>),
+    /// A range of the buffer output this many times.
+    Repeat(Range<usize>, usize),
+}
+
+/// How many copies of a terminator are expanded at once when outputting a
+/// run, bounding the buffer to a few KiB per run.
+const BLOCK_REPEATS: usize = 1024;
+
+impl Stripper {
+    /// Creates a stripper for a new stream.
+    pub fn new(options: Options) -> Self {
+        let next_progress = options.progress.as_ref().map_or(u64::MAX, |p| p.every);
+        Self {
+            options,
+            pending: Vec::new(),
+            pending_bytes: 0,
+            carry: Vec::new(),
+            started: false,
+            buffer: Vec::new(),
+            segments: Vec::new(),
+            stats: Stats::default(),
+            next_progress,
+        }
+    }
+
+    /// Starts over with a new stream and `options`, keeping the memory
+    /// allocated for the previous one, so that processing many small
+    /// streams doesn't allocate for each.
+    pub fn reset(&mut self, options: Options) {
+        self.next_progress = options.progress.as_ref().map_or(u64::MAX, |p| p.every);
+        self.options = options;
+        self.pending.clear();
+        self.pending_bytes = 0;
+        self.carry.clear();
+        self.started = false;
+        self.buffer.clear();
+        self.segments.clear();
+        self.stats = Stats::default();
+    }
+
+    /// Processes the next chunk of input, returning the pieces of output that
+    /// are known so far.
+    ///
+    /// ## Errors
+    ///
+    /// This function will return [`Error::Limit`] if more terminators are held
+    /// back than [`OptionsBuilder::max_pending_bytes`] allows, and
+    /// [`Error::Cancelled`] without processing the chunk if the
+    /// [`OptionsBuilder::cancel_flag`] is set.
+    ///
+    /// [`OptionsBuilder::max_pending_bytes`]: crate::OptionsBuilder::max_pending_bytes
+    /// [`OptionsBuilder::cancel_flag`]: crate::OptionsBuilder::cancel_flag
+    pub fn push<'a>(
+        &'a mut self,
+        chunk: &'a [u8],
+    ) -> Result<impl Iterator<Item = &'a [u8]>, Error> {
+        if self.options.is_cancelled() {
+            return Err(Error::Cancelled);
+        }
+        self.buffer.clear();
+        self.segments.clear();
+        self.stats.bytes_in += chunk.len() as u64;
+        self.stats.chunks += 1;
+        if self.options.is_plain() {
+            self.push_plain(chunk);
+        } else {
+            self.push_tokens(chunk);
+        }
+        if self.stats.bytes_in >= self.next_progress {
+            self.report_progress();
+        }
+        if let Some(max) = self.options.max_pending_bytes
+            && self.pending_bytes > max
+        {
+            return Err(Error::Limit(max));
+        }
+        Ok(self.output(chunk))
+    }
+
+    /// Signals the end of input, returning the pieces of output that remain:
+    /// whatever trailing terminators the options keep.
+    pub fn finish(&mut self) -> impl Iterator<Item = &[u8]> {
+        self.buffer.clear();
+        self.segments.clear();
+        if !self.carry.is_empty() {
+            // an unfinished terminator at the end is just content
+            let carry = std::mem::take(&mut self.carry);
+            self.buffered_content(&carry);
+        }
+        let keep = if self.options.strip_trailing {
+            self.options.keep_trailing
+        } else {
+            usize::MAX
+        };
+        self.flush_pending(keep);
+        self.report_progress();
+        self.output(&[])
+    }
+
+    /// Statistics about the stream so far.
+    pub fn stats(&self) -> Stats {
+        self.stats
+    }
+
+    pub(crate) fn options(&self) -> &Options {
+        &self.options
+    }
+
+    /// Whether the output of [`finish`](Self::finish) would end with a kept
+    /// terminator. An unfinished one at the end is content instead.
+    pub(crate) fn ends_with_terminator(&self) -> bool {
+        self.carry.is_empty() && !self.pending.is_empty()
+    }
+
+    /// Calls the progress callback, if any, and schedules the next call.
+    fn report_progress(&mut self) {
+        let Some(progress) = &self.options.progress else {
+            return;
+        };
+        let Stats {
+            bytes_in,
+            bytes_out,
+            ..
+        } = self.stats;
+        (progress.callback)(bytes_in, bytes_out);
+        self.next_progress = (bytes_in / progress.every + 1) * progress.every;
+    }
+
+    fn output<'a>(&'a self, chunk: &'a [u8]) -> impl Iterator<Item = &'a [u8]> {
+        self.segments.iter().flat_map(move |segment| {
+            let (piece, times) = match segment {
+                Segment::Chunk(range) => (&chunk[range.clone()], 1),
+                Segment::Buffer(range) => (&self.buffer[range.clone()], 1),
+                Segment::Repeat(range, times) => (&self.buffer[range.clone()], *times),
+            };
+            iter::repeat_n(piece, times)
+        })
+    }
+
+    fn push_tokens(&mut self, chunk: &[u8]) {
+        let mut start = 0;
+        if !self.carry.is_empty() {
+            // finish classifying the carried bytes with the start of the chunk
+            let carried = self.carry.len();
+            let mut joined = std::mem::take(&mut self.carry);
+            joined.extend_from_slice(&chunk[..chunk.len().min(MAX_TERMINATOR_LEN)]);
+            let mut i = 0;
+            while i < carried {
+                match self.options.policy().classify(&joined[i..]) {
+                    TerminatorMatch::Content => {
+                        self.buffered_content(&joined[i..=i]);
+                        i += 1;
+                    }
+                    TerminatorMatch::Terminator(len) => {
+                        self.terminator(&joined[i..i + len]);
+                        i += len;
+                    }
+                    TerminatorMatch::Incomplete => {
+                        // the chunk was too short to decide, so it's all in joined
+                        self.carry = joined.split_off(i);
+                        return;
+                    }
+                }
+            }
+            start = i - carried;
+        }
+
+        let mut content_start = start;
+        let mut i = start;
+        while let Some(candidate) = self.find_candidate(&chunk[i..]) {
+            i += candidate;
+            match self.options.policy().classify(&chunk[i..]) {
+                TerminatorMatch::Content => i += 1,
+                TerminatorMatch::Terminator(len) => {
+                    self.content(content_start..i);
+                    self.terminator(&chunk[i..i + len]);
+                    i += len;
+                    content_start = i;
+                }
+                TerminatorMatch::Incomplete => {
+                    self.content(content_start..i);
+                    self.carry.extend_from_slice(&chunk[i..]);
+                    return;
+                }
+            }
+        }
+        self.content(content_start..chunk.len());
+    }
+
+    /// Finds the next byte that may start a terminator.
+    fn find_candidate(&self, haystack: &[u8]) -> Option<usize> {
+        let policy = self.options.policy();
+        match policy.start_bytes() {
+            Some(start_bytes) => find_any(start_bytes, haystack),
+            None => haystack.iter().position(|&b| policy.may_start(b)),
+        }
+    }
+
+    /// Like [`push_tokens`](Self::push_tokens), but only terminators at the
+    /// edges of the chunk need looking at, since nothing in between is
+    /// changed.
+    fn push_plain(&mut self, chunk: &[u8]) {
+        let Some(last) = chunk.iter().rposition(|&b| !is_newline(b)) else {
+            self.newlines(chunk);
+            return;
+        };
+        // there is content, so this finds it
+        let first = chunk.iter().position(|&b| !is_newline(b)).unwrap_or(last);
+        self.newlines(&chunk[..first]);
+        self.content(first..last + 1);
+        self.newlines(&chunk[last + 1..]);
+    }
+
+    /// Queues the ASCII terminators making up `newlines`.
+    fn newlines(&mut self, newlines: &[u8]) {
+        let mut i = 0;
+        while i < newlines.len() {
+            if let TerminatorMatch::Terminator(len) = AsciiPolicy.classify(&newlines[i..]) {
+                self.terminator(&newlines[i..i + len]);
+                i += len;
+            }
+        }
+    }
+
+    fn terminator(&mut self, bytes: &[u8]) {
+        if bytes == b"\n"
+            && let Some(last) = self.pending.last_mut()
+            && last.terminator.as_bytes() == b"\r"
+        {
+            // a `\r\n` split across chunks
+            last.count -= 1;
+            self.pending_bytes -= 1;
+            if last.count == 0 {
+                self.pending.pop();
+            }
+            self.queue(Terminator::CRLF);
+        } else {
+            self.queue(Terminator::new(bytes));
+        }
+    }
+
+    fn queue(&mut self, terminator: Terminator) {
+        self.pending_bytes += u64::from(terminator.len);
+        match self.pending.last_mut() {
+            Some(last) if last.terminator == terminator => last.count += 1,
+            _ => self.pending.push(Run {
+                terminator,
+                count: 1,
+            }),
+        }
+    }
+
+    /// Outputs the given range of the pushed chunk as content.
+    fn content(&mut self, range: Range<usize>) {
+        if range.is_empty() {
+            return;
+        }
+        self.start_content(range.len());
+        self.segments.push(Segment::Chunk(range));
+    }
+
+    /// Outputs `content` as content, copying it to the buffer.
+    fn buffered_content(&mut self, content: &[u8]) {
+        if content.is_empty() {
+            return;
+        }
+        self.start_content(content.len());
+        let start = self.buffer.len();
+        self.buffer.extend_from_slice(content);
+        match self.segments.last_mut() {
+            Some(Segment::Buffer(last)) if last.end == start => last.end = self.buffer.len(),
+            _ => self
+                .segments
+                .push(Segment::Buffer(start..self.buffer.len())),
+        }
+    }
+
+    fn start_content(&mut self, len: usize) {
+        self.flush_pending(usize::MAX);
+        self.started = true;
+        self.stats.bytes_out += len as u64;
+    }
+
+    /// Outputs up to `limit` of the pending terminators, subject to the
+    /// leading and squeeze options, and clears them.
+    fn flush_pending(&mut self, limit: usize) {
+        let pending = self.pending.iter().map(|run| run.count).sum::<usize>();
+        let mut n = pending.min(limit);
+        if !self.started && self.options.strip_leading {
+            n = 0;
+        }
+        if self.options.squeeze {
+            n = n.min(if self.started { 2 } else { 1 });
+        }
+        for i in 0..self.pending.len() {
+            let Run { terminator, count } = self.pending[i];
+            let kept = count.min(n);
+            n -= kept;
+            let stripped = (count - kept) as u64;
+            let bytes = terminator.as_bytes();
+            self.stats.lf_stripped +=
+                stripped * bytes.iter().filter(|&&b| b == b'\n').count() as u64;
+            self.stats.cr_stripped +=
+                stripped * bytes.iter().filter(|&&b| b == b'\r').count() as u64;
+            match self.options.line_ending {
+                Some(line_ending) => {
+                    if line_ending.as_bytes() != bytes {
+                        self.stats.terminators_rewritten += kept as u64;
+                    }
+                    self.repeat(line_ending.as_bytes(), kept);
+                }
+                None => self.repeat(bytes, kept),
+            }
+        }
+        self.pending.clear();
+        self.pending_bytes = 0;
+    }
+
+    /// Outputs `bytes` the given number of times.
+    fn repeat(&mut self, bytes: &[u8], times: usize) {
+        if times == 0 {
+            return;
+        }
+        self.stats.bytes_out += (bytes.len() * times) as u64;
+        let start = self.buffer.len();
+        for _ in 0..times.min(BLOCK_REPEATS) {
+            self.buffer.extend_from_slice(bytes);
+        }
+        let block = start..self.buffer.len();
+        let remainder = times % BLOCK_REPEATS;
+        if times >= BLOCK_REPEATS {
+            self.segments
+                .push(Segment::Repeat(block, times / BLOCK_REPEATS));
+        }
+        if remainder > 0 {
+            self.segments
+                .push(Segment::Buffer(start..start + remainder * bytes.len()));
+        }
+    }
+}
+
+#[cfg(all(feature = "memchr", not(kani)))]
+fn find_any(needles: &[u8], haystack: &[u8]) -> Option<usize> {
+    match *needles {
+        [a] => memchr::memchr(a, haystack),
+        [a, b] => memchr::memchr2(a, b, haystack),
+        [a, b, c] => memchr::memchr3(a, b, c, haystack),
+        _ if needles.len() <= simd::MAX_NEEDLES => simd::find_any(needles, haystack),
+        _ => haystack.iter().position(|b| needles.contains(b)),
+    }
+}
+
+#[cfg(any(not(feature = "memchr"), kani))]
+fn find_any(needles: &[u8], haystack: &[u8]) -> Option<usize> {
+    if (1..=simd::MAX_NEEDLES).contains(&needles.len()) {
+        simd::find_any(needles, haystack)
+    } else {
+        haystack.iter().position(|b| needles.contains(b))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::BufReader;
+    use std::sync::Arc;
+    use std::sync::Mutex;
+
+    use crate::CrLfPolicy;
+    use crate::Error;
+    use crate::LfPolicy;
+    use crate::LineEnding;
+    use crate::Options;
+    use crate::PredicatePolicy;
+    use crate::Stripper;
+    use crate::UnicodePolicy;
+    use crate::process;
+    use crate::strip_trailing_newlines;
+
+    /// Processes `input` in chunks of every size from 1 to 8 bytes, asserting
+    /// that the output doesn't depend on the chunking, and returns it.
+    fn run(input: &str, options: &Options) -> String {
+        let mut whole = Vec::new();
+        process(input.as_bytes(), &mut whole, options).unwrap();
+        for capacity in 1..=8 {
+            let mut chunked = Vec::new();
+            let reader = BufReader::with_capacity(capacity, input.as_bytes());
+            process(reader, &mut chunked, options).unwrap();
+            assert_eq!(chunked, whole, "capacity {capacity}, input {input:?}");
+        }
+        String::from_utf8(whole).unwrap()
+    }
+
+    #[test]
+    fn test_default_matches_strip_trailing_newlines() {
+        for input in [
+            "",
+            "abc",
+            "\n",
+            "abc\r\n\r\n",
+            "\r\r\r",
+            "\n\nabc\n\n",
+            "a\rb\nc\r\nd\n",
+            "\r\nabc",
+        ] {
+            let mut expected = Vec::new();
+            strip_trailing_newlines(input.as_bytes(), &mut expected).unwrap();
+            assert_eq!(run(input, &Options::default()).as_bytes(), expected);
+        }
+    }
+
+    #[test]
+    fn test_keep_trailing() {
+        let options = Options::builder().keep_trailing(1).build();
+        assert_eq!(run("a\n\n", &options), "a\n");
+        assert_eq!(run("a\r\n\r\n", &options), "a\r\n");
+        assert_eq!(run("a", &options), "a");
+        assert_eq!(run("\n\n", &options), "\n");
+
+        let options = Options::builder().keep_trailing(2).build();
+        assert_eq!(run("a\r\n\r\n\r\n", &options), "a\r\n\r\n");
+    }
+
+    #[test]
+    fn test_no_strip_trailing() {
+        let options = Options::builder().strip_trailing(false).build();
+        assert_eq!(run("a\n\n", &options), "a\n\n");
+    }
+
+    #[test]
+    fn test_strip_leading() {
+        let options = Options::builder().strip_leading(true).build();
+        assert_eq!(run("\n\r\na\n\nb\n", &options), "a\n\nb");
+        assert_eq!(run("\n\n", &options), "");
+    }
+
+    #[test]
+    fn test_squeeze() {
+        let options = Options::builder().squeeze(true).build();
+        assert_eq!(run("\n\n\na\n\n\n\nb\nc\n\n", &options), "\na\n\nb\nc");
+        let options = Options::builder().squeeze(true).keep_trailing(5).build();
+        assert_eq!(run("a\n\n\n\n", &options), "a\n\n");
+    }
+
+    #[test]
+    fn test_line_ending() {
+        let options = Options::builder().line_ending(LineEnding::Lf).build();
+        assert_eq!(run("a\r\nb\rc\n\r\n", &options), "a\nb\nc");
+
+        let options = Options::builder()
+            .line_ending(LineEnding::CrLf)
+            .strip_trailing(false)
+            .build();
+        assert_eq!(run("a\nb\r\n\r", &options), "a\r\nb\r\n\r\n");
+    }
+
+    #[test]
+    fn test_unicode() {
+        let options = Options::builder().policy(UnicodePolicy).build();
+        assert_eq!(run("a\u{2028}b\u{85}\u{2029}\n", &options), "a\u{2028}b");
+        // other characters sharing a first byte with the terminators
+        assert_eq!(run("\u{a2}\u{2014}\u{85}", &options), "\u{a2}\u{2014}");
+        assert_eq!(run("a\u{2029}", &Options::default()), "a\u{2029}");
+
+        let options = Options::builder()
+            .policy(UnicodePolicy)
+            .line_ending(LineEnding::Lf)
+            .build();
+        assert_eq!(run("a\u{85}b\u{2028}c\u{2029}", &options), "a\nb\nc");
+    }
+
+    #[test]
+    fn test_stripper_pieces() {
+        let mut stripper = Stripper::new(Options::default());
+        let pieces: Vec<&[u8]> = stripper.push(b"\nab\r").unwrap().collect();
+        assert_eq!(pieces, [b"\n".as_slice(), b"ab"]);
+        let pieces: Vec<&[u8]> = stripper.push(b"\ncd\n").unwrap().collect();
+        assert_eq!(pieces, [b"\r\n".as_slice(), b"cd"]);
+        assert_eq!(stripper.finish().count(), 0);
+
+        let mut stripper = Stripper::new(Options::builder().keep_trailing(1).build());
+        assert_eq!(stripper.push(b"a\n\n").unwrap().count(), 1);
+        let pieces: Vec<&[u8]> = stripper.finish().collect();
+        assert_eq!(pieces, [b"\n".as_slice()]);
+    }
+
+    #[test]
+    fn test_reset() {
+        let mut stripper = Stripper::new(Options::default());
+        // a held back terminator, half a `\r\n` and some stats to forget
+        assert_eq!(stripper.push(b"a\n\r").unwrap().count(), 1);
+        stripper.reset(Options::builder().keep_trailing(1).build());
+        let pieces: Vec<&[u8]> = stripper.push(b"\nb\n\n").unwrap().collect();
+        assert_eq!(pieces, [b"\n".as_slice(), b"b"]);
+        let pieces: Vec<&[u8]> = stripper.finish().collect();
+        assert_eq!(pieces, [b"\n".as_slice()]);
+        assert_eq!(stripper.stats().bytes_in, 4);
+    }
+
+    #[test]
+    fn test_policies() {
+        let options = Options::builder().policy(LfPolicy).build();
+        assert_eq!(run("a\r\n\r\n\n", &options), "a\r\n\r");
+        assert_eq!(run("a\r", &options), "a\r");
+
+        let options = Options::builder().policy(CrLfPolicy).build();
+        assert_eq!(run("a\n\r\n\r\n", &options), "a\n");
+        assert_eq!(run("a\r\n\r", &options), "a\r\n\r");
+        assert_eq!(run("a\r\r\n", &options), "a\r");
+
+        let options = Options::builder()
+            .policy(PredicatePolicy(|b| b == b' ' || b == b'\n'))
+            .keep_trailing(1)
+            .build();
+        assert_eq!(run("a \nb\n \n", &options), "a \nb\n");
+    }
+
+    #[test]
+    fn test_long_runs_stay_small() {
+        let mut stripper = Stripper::new(Options::default());
+        let newlines = [b'\n'; 4096];
+        for _ in 0..1000 {
+            assert_eq!(stripper.push(&newlines).unwrap().count(), 0);
+        }
+        assert_eq!(stripper.pending.len(), 1);
+        let out: Vec<u8> = stripper
+            .push(b"\r\nx")
+            .unwrap()
+            .flatten()
+            .copied()
+            .collect();
+        assert!(stripper.buffer.len() <= 4096);
+        assert_eq!(out.len(), 4096 * 1000 + 3);
+        assert!(out[..4096 * 1000].iter().all(|&b| b == b'\n'));
+        assert_eq!(&out[4096 * 1000..], b"\r\nx");
+    }
+
+    #[test]
+    fn test_max_pending_bytes() {
+        let options = Options::builder().max_pending_bytes(4).build();
+        let mut out = Vec::new();
+        assert!(process(b"a\r\n\r\nb\n\n\n\n".as_slice(), &mut out, &options).is_ok());
+        let reader = BufReader::with_capacity(1, b"a\r\n\r\n\nb".as_slice());
+        let err = process(reader, &mut out, &options).unwrap_err();
+        assert!(matches!(err, Error::Limit(4)));
+    }
+
+    #[test]
+    fn test_stats() {
+        let input = b"\n\na\r\nb\r\n\n\r";
+        let options = Options::builder().strip_leading(true).build();
+        let mut out = Vec::new();
+        let reader = BufReader::with_capacity(2, input.as_slice());
+        let stats = process(reader, &mut out, &options).unwrap();
+        assert_eq!(out, b"a\r\nb");
+        assert_eq!(stats.bytes_in, 10);
+        assert_eq!(stats.bytes_out, 4);
+        assert_eq!(stats.lf_stripped, 4);
+        assert_eq!(stats.cr_stripped, 2);
+        assert_eq!(stats.chunks, 5);
+        assert_eq!(stats.terminators_rewritten, 0);
+
+        let options = Options::builder().line_ending(LineEnding::Lf).build();
+        let mut out = Vec::new();
+        let stats = process(b"a\rb\nc\r\nd\r".as_slice(), &mut out, &options).unwrap();
+        assert_eq!(out, b"a\nb\nc\nd");
+        assert_eq!(stats.terminators_rewritten, 2);
+    }
+
+    #[test]
+    fn test_progress() {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let options = Options::builder()
+            .on_progress(4, {
+                let calls = Arc::clone(&calls);
+                move |bytes_in, bytes_out| calls.lock().unwrap().push((bytes_in, bytes_out))
+            })
+            .build();
+        let mut stripper = Stripper::new(options);
+        for chunk in [b"ab".as_slice(), b"c\n", b"\n\nde", b"fghij\n"] {
+            stripper.push(chunk).unwrap().for_each(drop);
+        }
+        stripper.finish().for_each(drop);
+        assert_eq!(*calls.lock().unwrap(), [(4, 3), (8, 8), (14, 13), (14, 13)]);
+    }
+
+    #[test]
+    fn test_incomplete_terminator_at_end() {
+        let options = Options::builder().policy(UnicodePolicy).build();
+        let input = b"a\n\xE2\x80";
+        let mut out = Vec::new();
+        process(
+            BufReader::with_capacity(1, input.as_slice()),
+            &mut out,
+            &options,
+        )
+        .unwrap();
+        assert_eq!(out, input);
+    }
+}
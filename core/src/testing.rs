@@ -0,0 +1,111 @@
+//! Readers and writers that fail on demand, for testing error handling.
+
+use std::io;
+use std::io::ErrorKind;
+use std::io::Read;
+use std::io::Write;
+
+/// Reads `input` until `fail_after` bytes have been read, then fails.
+pub(crate) struct FailingReader<'a> {
+    pub(crate) input: &'a [u8],
+    pub(crate) fail_after: usize,
+}
+
+impl Read for FailingReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.fail_after == 0 {
+            return Err(io::Error::other("injected read error"));
+        }
+        let n = buf.len().min(self.input.len()).min(self.fail_after);
+        buf[..n].copy_from_slice(&self.input[..n]);
+        self.input = &self.input[n..];
+        self.fail_after -= n;
+        Ok(n)
+    }
+}
+
+/// Collects what is written, taking at most `max_write` bytes per call and
+/// failing once `fail_after` bytes have been written.
+pub(crate) struct FaultyWriter {
+    pub(crate) written: Vec<u8>,
+    pub(crate) max_write: usize,
+    pub(crate) fail_after: usize,
+}
+
+impl FaultyWriter {
+    /// Takes at most `max_write` bytes per call, and never fails.
+    pub(crate) fn short(max_write: usize) -> Self {
+        Self {
+            written: Vec::new(),
+            max_write,
+            fail_after: usize::MAX,
+        }
+    }
+
+    /// Takes everything up to `fail_after` bytes, then fails.
+    pub(crate) fn failing_after(fail_after: usize) -> Self {
+        Self {
+            written: Vec::new(),
+            max_write: usize::MAX,
+            fail_after,
+        }
+    }
+}
+
+impl Write for FaultyWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.fail_after == 0 && !buf.is_empty() {
+            return Err(io::Error::other("injected write error"));
+        }
+        let n = buf.len().min(self.max_write).min(self.fail_after);
+        self.written.extend_from_slice(&buf[..n]);
+        self.fail_after -= n;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Fails every other read or write with [`ErrorKind::Interrupted`], as a
+/// signal would.
+pub(crate) struct Interrupting<T> {
+    pub(crate) inner: T,
+    interrupt: bool,
+}
+
+impl<T> Interrupting<T> {
+    pub(crate) fn new(inner: T) -> Self {
+        Self {
+            inner,
+            interrupt: false,
+        }
+    }
+
+    fn check(&mut self) -> io::Result<()> {
+        self.interrupt = !self.interrupt;
+        if self.interrupt {
+            return Err(ErrorKind::Interrupted.into());
+        }
+        Ok(())
+    }
+}
+
+impl<R: Read> Read for Interrupting<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.check()?;
+        self.inner.read(buf)
+    }
+}
+
+impl<W: Write> Write for Interrupting<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.check()?;
+        self.inner.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
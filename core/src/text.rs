@@ -0,0 +1,142 @@
+//! A [`fmt::Write`] adapter over [`Stripper`].
+
+use std::fmt;
+use std::str;
+
+use crate::Options;
+use crate::Stats;
+use crate::Stripper;
+
+/// Wraps a [`fmt::Write`], such as a [`String`] or a [`fmt::Formatter`],
+/// stripping trailing newlines from everything written to it.
+///
+/// Like [`StripTrailingNewlines`](crate::StripTrailingNewlines), but for
+/// text. Output that comes straight from a written `&str` is passed on
+/// without validating it as UTF-8 again; only terminators that were held
+/// back are checked. Call [`finish`](Self::finish) once done.
+///
+/// ```
+/// use std::fmt::Write;
+///
+/// let mut writer = nln_core::StripTrailingNewlinesFmt::new(String::new());
+/// writeln!(writer, "hello")?;
+/// writeln!(writer)?;
+/// assert_eq!(writer.finish()?, "hello");
+/// # Ok::<(), std::fmt::Error>(())
+/// ```
+#[derive(Debug)]
+pub struct StripTrailingNewlinesFmt<W: fmt::Write> {
+    inner: W,
+    stripper: Stripper,
+    /// The start of a character split across pieces of output.
+    partial: Vec<u8>,
+}
+
+impl<W: fmt::Write> StripTrailingNewlinesFmt<W> {
+    /// Wraps `inner` with the default options.
+    pub fn new(inner: W) -> Self {
+        Self::with_options(inner, Options::default())
+    }
+
+    /// Wraps `inner`, processing what is written as configured by `options`.
+    pub fn with_options(inner: W, options: Options) -> Self {
+        Self {
+            inner,
+            stripper: Stripper::new(options),
+            partial: Vec::new(),
+        }
+    }
+
+    /// Writes out what is still held back, returning the inner writer.
+    ///
+    /// ## Errors
+    ///
+    /// This function will return an error if the inner writer fails, or if a
+    /// [`NewlinePolicy`](crate::NewlinePolicy) removed part of a character,
+    /// so that the output isn't valid UTF-8.
+    pub fn finish(mut self) -> Result<W, fmt::Error> {
+        for piece in self.stripper.finish() {
+            write_piece(&mut self.inner, &mut self.partial, "", piece)?;
+        }
+        if !self.partial.is_empty() {
+            return Err(fmt::Error);
+        }
+        Ok(self.inner)
+    }
+
+    /// Statistics about what has been written so far.
+    pub fn stats(&self) -> Stats {
+        self.stripper.stats()
+    }
+
+    /// The inner writer.
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+}
+
+impl<W: fmt::Write> fmt::Write for StripTrailingNewlinesFmt<W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let pieces = self.stripper.push(s.as_bytes()).map_err(|_| fmt::Error)?;
+        for piece in pieces {
+            write_piece(&mut self.inner, &mut self.partial, s, piece)?;
+        }
+        Ok(())
+    }
+}
+
+/// Writes `piece`, borrowing it from `s` if it is a part of it, and
+/// validating it otherwise.
+fn write_piece(
+    inner: &mut impl fmt::Write,
+    partial: &mut Vec<u8>,
+    s: &str,
+    piece: &[u8],
+) -> fmt::Result {
+    if partial.is_empty()
+        && let Some(text) = subslice(s, piece)
+    {
+        return inner.write_str(text);
+    }
+    partial.extend_from_slice(piece);
+    let valid = match str::from_utf8(partial) {
+        Ok(text) => text.len(),
+        // the rest of the character may come with the next piece
+        Err(err) if err.error_len().is_none() => err.valid_up_to(),
+        Err(_) => return Err(fmt::Error),
+    };
+    let text = str::from_utf8(&partial[..valid]).map_err(|_| fmt::Error)?;
+    inner.write_str(text)?;
+    partial.drain(..valid);
+    Ok(())
+}
+
+/// `piece` as a part of `s`, if it is one and starts and ends on character
+/// boundaries.
+fn subslice<'a>(s: &'a str, piece: &[u8]) -> Option<&'a str> {
+    let offset = (piece.as_ptr() as usize).checked_sub(s.as_ptr() as usize)?;
+    s.get(offset..offset.checked_add(piece.len())?)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fmt::Write;
+
+    use super::StripTrailingNewlinesFmt;
+    use crate::LineEnding;
+    use crate::Options;
+    use crate::UnicodePolicy;
+
+    #[test]
+    fn test_write_in_pieces() {
+        let options = Options::builder()
+            .policy(UnicodePolicy)
+            .line_ending(LineEnding::Lf)
+            .build();
+        let mut writer = StripTrailingNewlinesFmt::with_options(String::new(), options);
+        for piece in ["é\r", "\n\u{2028}", "€", "\u{2029}\n"] {
+            writer.write_str(piece).unwrap();
+        }
+        assert_eq!(writer.finish().unwrap(), "é\n\n€");
+    }
+}
@@ -0,0 +1,195 @@
+//! Running processing on a background thread.
+
+use std::io;
+use std::io::BufWriter;
+use std::io::ErrorKind;
+use std::io::Read;
+use std::io::Write;
+use std::sync::mpsc;
+use std::sync::mpsc::Receiver;
+use std::sync::mpsc::Sender;
+use std::sync::mpsc::SyncSender;
+use std::thread;
+use std::thread::JoinHandle;
+
+use crate::Error;
+use crate::Options;
+use crate::Stats;
+use crate::process_chunks;
+use crate::process_read;
+
+/// How many buffers [`process_pipelined`] can read ahead of processing.
+const PIPELINE_DEPTH: usize = 4;
+
+/// Moves bytes from `reader` to `writer` on a new thread, transforming
+/// newlines as configured by `options`, like [`process`](crate::process).
+///
+/// Both ends are buffered with [`Options::buffer_size`], so unbuffered
+/// readers and writers such as pipes and sockets can be passed as they are.
+/// Join the returned handle for the outcome.
+///
+/// ```
+/// let handle = nln_core::spawn_strip(b"a\n\n".as_slice(), Vec::new(), nln_core::Options::default())?;
+/// let stats = handle.join().expect("the thread panicked")?;
+/// assert_eq!(stats.bytes_out, 1);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+///
+/// ## Errors
+///
+/// This function will return an error if the thread cannot be spawned. The
+/// thread's result is an [`Error`] as returned by
+/// [`process`](crate::process).
+pub fn spawn_strip(
+    reader: impl Read + Send + 'static,
+    writer: impl Write + Send + 'static,
+    options: Options,
+) -> io::Result<JoinHandle<Result<Stats, Error>>> {
+    thread::Builder::new()
+        .name("nln".to_string())
+        .spawn(move || {
+            let mut writer = BufWriter::with_capacity(options.buffer_size(), writer);
+            process_read(reader, &mut writer, &options)
+        })
+}
+
+/// Like [`process_read`], but reads on a separate thread, so reading overlaps
+/// with processing and writing.
+///
+/// The reading thread fills a small ring of buffers of
+/// [`Options::buffer_size`] bytes, which are handed back once processed. This
+/// pays off for large streams whose reads and writes both block, such as
+/// pipes between slow processes. If writing fails, this returns only once the
+/// read in progress does.
+///
+/// ```
+/// let mut out = Vec::new();
+/// nln_core::process_pipelined(b"a\n\n".as_slice(), &mut out, &nln_core::Options::default())?;
+/// assert_eq!(out, b"a");
+/// # Ok::<(), nln_core::Error>(())
+/// ```
+///
+/// ## Errors
+///
+/// This function will return [`Error::Read`] if the reader cannot be read and
+/// [`Error::Write`] if the writer cannot be written to.
+pub fn process_pipelined(
+    reader: impl Read + Send,
+    o: &mut impl Write,
+    options: &Options,
+) -> Result<Stats, Error> {
+    let (filled, chunks) = mpsc::sync_channel(PIPELINE_DEPTH);
+    let (pool, empty) = mpsc::channel();
+    for _ in 0..PIPELINE_DEPTH {
+        // only fails once the receiver is gone
+        let _ = pool.send(Vec::new());
+    }
+    let size = options.buffer_size();
+    thread::scope(|scope| {
+        scope.spawn(move || fill(reader, size, &empty, &filled));
+        // the channels close when this returns, which stops the reader
+        let chunks = chunks.into_iter().map(move |chunk| {
+            chunk.map(|buffer| Recycled {
+                buffer,
+                pool: pool.clone(),
+            })
+        });
+        process_chunks(chunks, o, options)
+    })
+}
+
+/// Reads into the buffers from `empty` and sends them on as `filled`, until
+/// the end of input, an error, or either channel closing.
+fn fill(
+    mut reader: impl Read,
+    size: usize,
+    empty: &Receiver<Vec<u8>>,
+    filled: &SyncSender<io::Result<Vec<u8>>>,
+) {
+    while let Ok(mut buffer) = empty.recv() {
+        buffer.resize(size, 0);
+        let read = loop {
+            match reader.read(&mut buffer) {
+                Err(err) if err.kind() == ErrorKind::Interrupted => {}
+                read => break read,
+            }
+        };
+        let chunk = match read {
+            Ok(0) => return,
+            Ok(n) => {
+                buffer.truncate(n);
+                Ok(buffer)
+            }
+            Err(err) => Err(err),
+        };
+        let failed = chunk.is_err();
+        if filled.send(chunk).is_err() || failed {
+            return;
+        }
+    }
+}
+
+/// A filled buffer, which goes back to the reading thread once dropped.
+struct Recycled {
+    buffer: Vec<u8>,
+    pool: Sender<Vec<u8>>,
+}
+
+impl AsRef<[u8]> for Recycled {
+    fn as_ref(&self) -> &[u8] {
+        &self.buffer
+    }
+}
+
+impl Drop for Recycled {
+    fn drop(&mut self) {
+        // the reader may be done already
+        let _ = self.pool.send(std::mem::take(&mut self.buffer));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+    use std::io::Read;
+
+    use super::process_pipelined;
+    use crate::Error;
+    use crate::Options;
+
+    /// Yields its input a few bytes at a time, then fails if asked to.
+    struct Trickle<'a> {
+        input: &'a [u8],
+        fail: bool,
+    }
+
+    impl Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            if self.input.is_empty() && self.fail {
+                return Err(io::Error::other("broken"));
+            }
+            let n = buf.len().min(self.input.len()).min(3);
+            buf[..n].copy_from_slice(&self.input[..n]);
+            self.input = &self.input[n..];
+            Ok(n)
+        }
+    }
+
+    #[test]
+    fn test_process_pipelined() {
+        let input = b"a\n\nb\r\n\n\nc\n\r\n\n";
+        let options = Options::builder().buffer_size(2).build();
+        let mut out = Vec::new();
+        let stats = process_pipelined(Trickle { input, fail: false }, &mut out, &options).unwrap();
+        assert_eq!(out, b"a\n\nb\r\n\n\nc");
+        assert_eq!(stats.bytes_in, input.len() as u64);
+
+        let result = process_pipelined(Trickle { input, fail: true }, &mut Vec::new(), &options);
+        assert!(matches!(result, Err(Error::Read(_))));
+
+        // the reader stops instead of waiting for buffers that never return
+        let endless = io::repeat(b'a');
+        let result = process_pipelined(endless, &mut [0; 4].as_mut_slice(), &options);
+        assert!(matches!(result, Err(Error::Write(_))));
+    }
+}
@@ -0,0 +1,45 @@
+//! JavaScript bindings through [`wasm_bindgen`].
+
+use wasm_bindgen::prelude::wasm_bindgen;
+
+use crate::Options;
+use crate::Stripper;
+
+/// Returns `input` without its trailing newlines.
+#[wasm_bindgen(js_name = stripTrailingNewlines)]
+pub fn strip_trailing_newlines(input: &[u8]) -> Vec<u8> {
+    crate::strip_trailing_newlines_bytes(input).to_vec()
+}
+
+/// A stripper for input arriving in chunks, exported as `Stripper`.
+#[wasm_bindgen(js_name = Stripper)]
+pub struct WasmStripper(Stripper);
+
+#[wasm_bindgen(js_class = Stripper)]
+impl WasmStripper {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Self {
+        Self(Stripper::new(Options::default()))
+    }
+
+    /// Processes the next chunk of input, returning the output known so far.
+    pub fn push(&mut self, chunk: &[u8]) -> Vec<u8> {
+        self.0
+            .push(chunk)
+            .expect("the default options have no limit or cancel flag")
+            .flatten()
+            .copied()
+            .collect()
+    }
+
+    /// Signals the end of input, returning the remaining output.
+    pub fn finish(&mut self) -> Vec<u8> {
+        self.0.finish().flatten().copied().collect()
+    }
+}
+
+impl Default for WasmStripper {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,331 @@
+//! [`Write`] adapters over [`Stripper`].
+
+use std::io::Result;
+use std::io::Write;
+
+use crate::LineEnding;
+use crate::Options;
+use crate::Stats;
+use crate::Stripper;
+use crate::write_pieces;
+
+/// Wraps a writer, stripping trailing newlines from everything written to it.
+///
+/// Newlines are held back until more content is written, so the inner writer
+/// never sees the trailing ones. Call [`finish`](Self::finish) once done to
+/// write out anything still held back that the options keep, such as with
+/// [`OptionsBuilder::keep_trailing`](crate::OptionsBuilder::keep_trailing);
+/// dropping the adapter without it loses that output.
+///
+/// ```
+/// use std::io::Write;
+///
+/// let mut writer = nln_core::StripTrailingNewlines::new(Vec::new());
+/// writeln!(writer, "hello")?;
+/// writeln!(writer)?;
+/// let out = writer.finish()?;
+/// assert_eq!(out, b"hello");
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Debug)]
+pub struct StripTrailingNewlines<W: Write> {
+    inner: W,
+    stripper: Stripper,
+}
+
+impl<W: Write> StripTrailingNewlines<W> {
+    /// Wraps `inner` with the default options.
+    pub fn new(inner: W) -> Self {
+        Self::with_options(inner, Options::default())
+    }
+
+    /// Wraps `inner`, processing what is written as configured by `options`.
+    pub fn with_options(inner: W, options: Options) -> Self {
+        Self {
+            inner,
+            stripper: Stripper::new(options),
+        }
+    }
+
+    /// Writes out what is still held back and flushes, returning the inner
+    /// writer.
+    ///
+    /// ## Errors
+    ///
+    /// This function will return an error if the inner writer cannot be
+    /// written to.
+    pub fn finish(mut self) -> Result<W> {
+        write_pieces(&mut self.inner, self.stripper.finish())?;
+        event!(debug, stats = ?self.stripper.stats(), "finished writing");
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+
+    /// Statistics about what has been written so far.
+    pub fn stats(&self) -> Stats {
+        self.stripper.stats()
+    }
+
+    /// The inner writer.
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Writing to the inner writer directly bypasses the stripping.
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+}
+
+impl<W: Write> Write for StripTrailingNewlines<W> {
+    /// Accepts all of `buf`. If the inner writer fails, part of the output
+    /// for `buf` may have been written.
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        event!(trace, bytes = buf.len(), "write chunk");
+        write_pieces(&mut self.inner, self.stripper.push(buf)?)?;
+        Ok(buf.len())
+    }
+
+    /// Flushes the inner writer. Held back newlines stay held back.
+    fn flush(&mut self) -> Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Wraps a writer, making sure that what is written to it ends with exactly
+/// one terminator, as POSIX expects of text files.
+///
+/// Extra trailing terminators are stripped, and [`finish`](Self::finish) adds
+/// one if there was none. The one kept is the last one written, such as
+/// `\r\n`; an added one is `\n`, or the
+/// [`OptionsBuilder::line_ending`](crate::OptionsBuilder::line_ending) if set.
+/// If nothing is written, nothing is output.
+///
+/// ```
+/// use std::io::Write;
+///
+/// let mut writer = nln_core::EnsureTrailingNewline::new(Vec::new());
+/// write!(writer, "fn main() {{}}")?;
+/// assert_eq!(writer.finish()?, b"fn main() {}\n");
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Debug)]
+pub struct EnsureTrailingNewline<W: Write>(StripTrailingNewlines<W>);
+
+impl<W: Write> EnsureTrailingNewline<W> {
+    /// Wraps `inner` with the default options.
+    pub fn new(inner: W) -> Self {
+        Self::with_options(inner, Options::default())
+    }
+
+    /// Wraps `inner`, processing what is written as configured by `options`.
+    /// The trailing terminators are handled as described above, whatever
+    /// `options` say about them.
+    pub fn with_options(inner: W, mut options: Options) -> Self {
+        options.strip_trailing = true;
+        options.keep_trailing = 1;
+        Self(StripTrailingNewlines::with_options(inner, options))
+    }
+
+    /// Writes out the final terminator and flushes, returning the inner
+    /// writer.
+    ///
+    /// ## Errors
+    ///
+    /// This function will return an error if the inner writer cannot be
+    /// written to.
+    pub fn finish(self) -> Result<W> {
+        let StripTrailingNewlines {
+            mut inner,
+            mut stripper,
+        } = self.0;
+        let line_ending = stripper.options().line_ending.unwrap_or(LineEnding::Lf);
+        let kept = stripper.ends_with_terminator();
+        write_pieces(&mut inner, stripper.finish())?;
+        if !kept && stripper.stats().bytes_out > 0 {
+            inner.write_all(line_ending.as_bytes())?;
+        }
+        inner.flush()?;
+        Ok(inner)
+    }
+
+    /// Statistics about what has been written so far.
+    pub fn stats(&self) -> Stats {
+        self.0.stats()
+    }
+
+    /// The inner writer.
+    pub fn get_ref(&self) -> &W {
+        self.0.get_ref()
+    }
+}
+
+impl<W: Write> Write for EnsureTrailingNewline<W> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        self.0.write(buf)
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.0.flush()
+    }
+}
+
+/// Wraps a writer, rewriting every terminator written to it as one line
+/// ending and leaving everything else alone, like `dos2unix` or `unix2dos`.
+///
+/// A `\r` at the end of one write is held back until the next shows whether
+/// a `\n` follows it. Call [`finish`](Self::finish) once done to write out
+/// anything still held back. Wrap a [`StripTrailingNewlines`] to also strip
+/// trailing newlines, though [`OptionsBuilder::line_ending`] does both in one
+/// pass.
+///
+/// [`OptionsBuilder::line_ending`]: crate::OptionsBuilder::line_ending
+///
+/// ```
+/// use std::io::Write;
+///
+/// use nln_core::LineEnding;
+///
+/// let mut writer = nln_core::LineNormalizer::new(Vec::new(), LineEnding::Lf);
+/// writer.write_all(b"a\r")?;
+/// writer.write_all(b"\nb\r\n\r\n")?;
+/// assert_eq!(writer.finish()?, b"a\nb\n\n");
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Debug)]
+pub struct LineNormalizer<W: Write>(StripTrailingNewlines<W>);
+
+impl<W: Write> LineNormalizer<W> {
+    /// Wraps `inner`, rewriting terminators as `line_ending`.
+    pub fn new(inner: W, line_ending: LineEnding) -> Self {
+        let options = Options::builder()
+            .strip_trailing(false)
+            .line_ending(line_ending)
+            .build();
+        Self(StripTrailingNewlines::with_options(inner, options))
+    }
+
+    /// Writes out what is still held back and flushes, returning the inner
+    /// writer.
+    ///
+    /// ## Errors
+    ///
+    /// This function will return an error if the inner writer cannot be
+    /// written to.
+    pub fn finish(self) -> Result<W> {
+        self.0.finish()
+    }
+
+    /// Statistics about what has been written so far.
+    pub fn stats(&self) -> Stats {
+        self.0.stats()
+    }
+
+    /// The inner writer.
+    pub fn get_ref(&self) -> &W {
+        self.0.get_ref()
+    }
+}
+
+impl<W: Write> Write for LineNormalizer<W> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        self.0.write(buf)
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.0.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use super::EnsureTrailingNewline;
+    use super::LineNormalizer;
+    use super::StripTrailingNewlines;
+    use crate::CrLfPolicy;
+    use crate::Options;
+    use crate::UnicodePolicy;
+
+    #[test]
+    fn test_write_in_pieces() {
+        let mut writer = StripTrailingNewlines::new(Vec::new());
+        for piece in ["\n", "a\r", "\nb", "\n\r", "\n", ""] {
+            writer.write_all(piece.as_bytes()).unwrap();
+        }
+        assert_eq!(writer.get_ref(), b"\na\r\nb");
+        assert_eq!(writer.finish().unwrap(), b"\na\r\nb");
+    }
+
+    #[test]
+    fn test_finish_keeps_trailing() {
+        let options = Options::builder().keep_trailing(1).build();
+        let mut writer = StripTrailingNewlines::with_options(Vec::new(), options);
+        writer.write_all(b"a\n\n").unwrap();
+        assert_eq!(writer.get_ref(), b"a");
+        assert_eq!(writer.finish().unwrap(), b"a\n");
+    }
+
+    #[test]
+    fn test_ensure_trailing_newline() {
+        for (input, expected) in [
+            ("", ""),
+            ("\n\n", "\n"),
+            ("a", "a\n"),
+            ("a\n", "a\n"),
+            ("a\r\n\n\n", "a\r\n"),
+        ] {
+            let mut writer = EnsureTrailingNewline::new(Vec::new());
+            writer.write_all(input.as_bytes()).unwrap();
+            assert_eq!(writer.finish().unwrap(), expected.as_bytes(), "{input:?}");
+        }
+
+        let options = Options::builder()
+            .line_ending(crate::LineEnding::CrLf)
+            .build();
+        let mut writer = EnsureTrailingNewline::with_options(Vec::new(), options);
+        writer.write_all(b"a\nb").unwrap();
+        assert_eq!(writer.finish().unwrap(), b"a\r\nb\r\n");
+
+        // an unfinished terminator at the end is content, so one is added
+        for (options, input, expected) in [
+            (
+                Options::builder().policy(CrLfPolicy).build(),
+                &b"a\r"[..],
+                &b"a\r\n"[..],
+            ),
+            (
+                Options::builder().policy(CrLfPolicy).build(),
+                b"a\r\n\r\n",
+                b"a\r\n",
+            ),
+            (
+                Options::builder().policy(UnicodePolicy).build(),
+                b"a\xE2",
+                b"a\xE2\n",
+            ),
+            (
+                Options::builder().policy(UnicodePolicy).build(),
+                b"a\xE2\x80\xA8\n\xE2\x80",
+                b"a\xE2\x80\xA8\n\xE2\x80\n",
+            ),
+        ] {
+            let mut writer = EnsureTrailingNewline::with_options(Vec::new(), options);
+            writer.write_all(input).unwrap();
+            assert_eq!(writer.finish().unwrap(), expected, "{input:?}");
+        }
+    }
+
+    #[test]
+    fn test_line_normalizer() {
+        let inner = StripTrailingNewlines::new(Vec::new());
+        let mut writer = LineNormalizer::new(inner, crate::LineEnding::CrLf);
+        for piece in ["a\r", "\n\nb\r", "\r", "\n\n"] {
+            writer.write_all(piece.as_bytes()).unwrap();
+        }
+        let inner = writer.finish().unwrap();
+        assert_eq!(inner.get_ref(), b"a\r\n\r\nb");
+        assert_eq!(inner.finish().unwrap(), b"a\r\n\r\nb");
+    }
+}
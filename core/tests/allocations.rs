@@ -0,0 +1,107 @@
+//! Checks that processing a stream allocates only while warming up, so that
+//! the steady state of a long stream doesn't touch the heap.
+
+use std::alloc::GlobalAlloc;
+use std::alloc::Layout;
+use std::alloc::System;
+use std::cell::Cell;
+use std::io;
+use std::io::BufReader;
+use std::io::Read;
+
+use nln_core::LineEnding;
+use nln_core::Options;
+use nln_core::UnicodePolicy;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Counts the allocations of each thread, so tests running in parallel
+/// don't see each other's.
+struct Counting;
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.with(|count| count.set(count.get() + 1));
+        // SAFETY: passed on as is
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        // SAFETY: passed on as is
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.with(|count| count.set(count.get() + 1));
+        // SAFETY: passed on as is
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: Counting = Counting;
+
+/// How often the test input repeats its pattern.
+const TIMES: usize = 100;
+
+fn allocations() -> usize {
+    ALLOCATIONS.with(Cell::get)
+}
+
+/// Repeats `pattern` `times` times, noting the allocations so far halfway
+/// through, once every alignment of chunks to the pattern has come by, and
+/// when it is done.
+struct Repeat<'a> {
+    pattern: &'a [u8],
+    offset: usize,
+    times: usize,
+    warm: Option<usize>,
+    done: Option<usize>,
+}
+
+impl Read for Repeat<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.times == 0 {
+            self.done.get_or_insert_with(allocations);
+            return Ok(0);
+        }
+        let n = buf.len().min(self.pattern.len() - self.offset);
+        buf[..n].copy_from_slice(&self.pattern[self.offset..self.offset + n]);
+        self.offset += n;
+        if self.offset == self.pattern.len() {
+            self.offset = 0;
+            self.times -= 1;
+            if self.times == TIMES / 2 {
+                self.warm = Some(allocations());
+            }
+        }
+        Ok(n)
+    }
+}
+
+#[test]
+fn test_steady_state_does_not_allocate() {
+    let pattern = b"content\n\n\r\n\r\rmore\n\n\n\n\n\n\n\n\n\n\ntext\r\n".repeat(100);
+    for options in [
+        Options::default(),
+        Options::builder().keep_trailing(1).squeeze(true).build(),
+        Options::builder().line_ending(LineEnding::CrLf).build(),
+        Options::builder().policy(UnicodePolicy).build(),
+        Options::builder().coalesce_writes(1 << 16).build(),
+    ] {
+        for capacity in [7, 4096] {
+            let mut input = Repeat {
+                pattern: &pattern,
+                offset: 0,
+                times: TIMES,
+                warm: None,
+                done: None,
+            };
+            let reader = BufReader::with_capacity(capacity, &mut input);
+            nln_core::process(reader, &mut io::sink(), &options).unwrap();
+            assert_eq!(input.warm, input.done, "{options:?}, capacity {capacity}");
+        }
+    }
+}
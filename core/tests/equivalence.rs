@@ -0,0 +1,286 @@
+//! Runs every way of processing a stream over the same inputs, options and
+//! chunkings, and checks that they agree on the output and statistics, so
+//! the implementations can't drift apart.
+
+use std::io;
+use std::io::BufRead;
+use std::io::Read;
+use std::io::Write;
+
+use nln_core::CrLfPolicy;
+use nln_core::LfPolicy;
+use nln_core::LineEnding;
+use nln_core::Options;
+use nln_core::Stats;
+use nln_core::StripTrailingNewlines;
+use nln_core::StrippedReader;
+use nln_core::Stripper;
+use nln_core::UnicodePolicy;
+
+const INPUTS: [&[u8]; 12] = [
+    b"",
+    b"\n\n\n",
+    b"\r\n\r\r\n",
+    b"abc",
+    b"abc\n\n",
+    b"\n\nabc\r\n\r\n\ndef\n\r\n",
+    b"a\r\rb\r\r\r",
+    b"a\n\n\n\nb\n\n\n\nc\r\n\r\n\r\n\r\n",
+    b"\r\n\r\na\r\n\nb\r\n\n\r\r\n",
+    "a\u{85}b\u{2028}\u{2029}\r\n\u{85}".as_bytes(),
+    b"a\xC2\n\xE2\x80\r\n\xE2\x80",
+    b"\0\n\0\r\0\r\n",
+];
+
+fn options() -> Vec<Options> {
+    vec![
+        Options::default(),
+        Options::builder().keep_trailing(1).build(),
+        Options::builder().keep_trailing(2).squeeze(true).build(),
+        Options::builder().strip_leading(true).build(),
+        Options::builder()
+            .strip_trailing(false)
+            .squeeze(true)
+            .build(),
+        Options::builder().line_ending(LineEnding::Lf).build(),
+        Options::builder()
+            .keep_trailing(1)
+            .line_ending(LineEnding::CrLf)
+            .build(),
+        Options::builder().policy(LfPolicy).keep_trailing(1).build(),
+        Options::builder().policy(CrLfPolicy).squeeze(true).build(),
+        Options::builder()
+            .policy(UnicodePolicy)
+            .strip_leading(true)
+            .build(),
+    ]
+}
+
+/// Processes the input, given in chunks, returning the output and the
+/// statistics if the implementation reports them.
+type Implementation = fn(&[&[u8]], &Options) -> (Vec<u8>, Option<Stats>);
+
+/// Hands out `chunks` one at a time, like a pipe.
+struct Chunked<'a> {
+    chunks: &'a [&'a [u8]],
+    /// How much of the first chunk has been consumed.
+    pos: usize,
+}
+
+impl<'a> Chunked<'a> {
+    fn new(chunks: &'a [&'a [u8]]) -> Self {
+        Self { chunks, pos: 0 }
+    }
+}
+
+impl Read for Chunked<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.fill_buf()?.read(buf)?;
+        self.consume(n);
+        Ok(n)
+    }
+}
+
+impl BufRead for Chunked<'_> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        while let [first, rest @ ..] = self.chunks {
+            if self.pos < first.len() {
+                return Ok(&first[self.pos..]);
+            }
+            self.chunks = rest;
+            self.pos = 0;
+        }
+        Ok(&[])
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.pos += amt;
+    }
+}
+
+fn implementations() -> Vec<(&'static str, Implementation)> {
+    // the rest are behind features
+    #[allow(unused_mut)]
+    let mut implementations: Vec<(&'static str, Implementation)> = vec![
+        ("process_bytes", |chunks, options| {
+            (
+                nln_core::process_bytes(&chunks.concat(), options).into_owned(),
+                None,
+            )
+        }),
+        ("process", |chunks, options| {
+            let mut out = Vec::new();
+            let stats = nln_core::process(Chunked::new(chunks), &mut out, options).unwrap();
+            (out, Some(stats))
+        }),
+        ("process_read", |chunks, options| {
+            let mut out = Vec::new();
+            let stats = nln_core::process_read(Chunked::new(chunks), &mut out, options).unwrap();
+            (out, Some(stats))
+        }),
+        ("process_chunks", |chunks, options| {
+            let mut out = Vec::new();
+            let chunks = chunks.iter().map(Ok);
+            let stats = nln_core::process_chunks(chunks, &mut out, options).unwrap();
+            (out, Some(stats))
+        }),
+        ("process_pipelined", |chunks, options| {
+            let mut out = Vec::new();
+            let stats =
+                nln_core::process_pipelined(Chunked::new(chunks), &mut out, options).unwrap();
+            (out, Some(stats))
+        }),
+        ("Stripper", |chunks, options| {
+            let mut stripper = Stripper::new(options.clone());
+            let mut out = Vec::new();
+            for chunk in chunks {
+                for piece in stripper.push(chunk).unwrap() {
+                    out.extend_from_slice(piece);
+                }
+            }
+            for piece in stripper.finish() {
+                out.extend_from_slice(piece);
+            }
+            (out, Some(stripper.stats()))
+        }),
+        ("StripTrailingNewlines", |chunks, options| {
+            let mut writer = StripTrailingNewlines::with_options(Vec::new(), options.clone());
+            for chunk in chunks {
+                writer.write_all(chunk).unwrap();
+            }
+            (writer.finish().unwrap(), None)
+        }),
+        ("StrippedReader", |chunks, options| {
+            let mut reader = StrippedReader::with_options(Chunked::new(chunks), options.clone());
+            let mut out = Vec::new();
+            reader.read_to_end(&mut out).unwrap();
+            (out, Some(reader.stats()))
+        }),
+    ];
+    #[cfg(feature = "futures-io")]
+    implementations.push(("process_async", |chunks, options| {
+        use std::pin::pin;
+        use std::task::Context;
+        use std::task::Poll;
+        use std::task::Waker;
+
+        let input = chunks.concat();
+        let mut out = Vec::new();
+        let stats = {
+            let future = pin!(nln_core::process_async(input.as_slice(), &mut out, options));
+            // in-memory readers and writers are always ready
+            let Poll::Ready(stats) = future.poll(&mut Context::from_waker(Waker::noop())) else {
+                panic!("in-memory processing should not be pending");
+            };
+            stats.unwrap()
+        };
+        (out, Some(stats))
+    }));
+    #[cfg(feature = "bytes")]
+    implementations.push(("BytesStripper", |chunks, options| {
+        let mut stripper = nln_core::BytesStripper::new(options.clone());
+        let mut out = Vec::new();
+        for chunk in chunks {
+            let frame = bytes::Bytes::copy_from_slice(chunk);
+            for piece in stripper.push(&frame).unwrap() {
+                out.extend_from_slice(&piece);
+            }
+        }
+        for piece in stripper.finish() {
+            out.extend_from_slice(&piece);
+        }
+        (out, Some(stripper.stats()))
+    }));
+    #[cfg(feature = "stream")]
+    implementations.push(("StripTrailingNewlinesStream", |chunks, options| {
+        use std::pin::Pin;
+        use std::task::Context;
+        use std::task::Poll;
+        use std::task::Waker;
+
+        use futures_core::Stream;
+
+        /// Yields the chunks, always ready.
+        struct Ready(std::vec::IntoIter<bytes::Bytes>);
+
+        impl Stream for Ready {
+            type Item = io::Result<bytes::Bytes>;
+
+            fn poll_next(
+                mut self: Pin<&mut Self>,
+                _: &mut Context<'_>,
+            ) -> Poll<Option<Self::Item>> {
+                Poll::Ready(self.0.next().map(Ok))
+            }
+        }
+
+        let frames = chunks
+            .iter()
+            .map(|chunk| bytes::Bytes::copy_from_slice(chunk));
+        let inner = Ready(frames.collect::<Vec<_>>().into_iter());
+        let mut stream =
+            nln_core::StripTrailingNewlinesStream::with_options(inner, options.clone());
+        let mut out = Vec::new();
+        let mut cx = Context::from_waker(Waker::noop());
+        while let Poll::Ready(Some(frame)) = Pin::new(&mut stream).poll_next(&mut cx) {
+            out.extend_from_slice(&frame.unwrap());
+        }
+        (out, Some(stream.stats()))
+    }));
+    #[cfg(feature = "tokio-util")]
+    implementations.push(("StripTrailingNewlinesCodec", |chunks, options| {
+        use bytes::BytesMut;
+        use tokio_util::codec::Decoder;
+
+        let mut codec = nln_core::StripTrailingNewlinesCodec::with_options(options.clone());
+        let mut out = Vec::new();
+        let mut src = BytesMut::new();
+        for chunk in chunks {
+            src.extend_from_slice(chunk);
+            while let Some(frame) = codec.decode(&mut src).unwrap() {
+                out.extend_from_slice(&frame);
+            }
+        }
+        while let Some(frame) = codec.decode_eof(&mut src).unwrap() {
+            out.extend_from_slice(&frame);
+        }
+        (out, Some(codec.stats()))
+    }));
+    implementations
+}
+
+/// The statistics that don't depend on how the input was split up.
+fn totals(stats: Stats) -> (u64, u64, u64, u64) {
+    (
+        stats.bytes_in,
+        stats.bytes_out,
+        stats.lf_stripped,
+        stats.cr_stripped,
+    )
+}
+
+#[test]
+fn test_implementations_agree() {
+    let implementations = implementations();
+    for input in INPUTS {
+        for options in options() {
+            let expected = nln_core::process_bytes(input, &options);
+            let mut stripper = Stripper::new(options.clone());
+            let _ = stripper.push(input).unwrap().count();
+            let _ = stripper.finish().count();
+            let expected_stats = totals(stripper.stats());
+
+            for size in [1, 2, 3, 7, input.len().max(1)] {
+                let chunks: Vec<&[u8]> = input.chunks(size).collect();
+                for (name, implementation) in &implementations {
+                    let (out, stats) = implementation(&chunks, &options);
+                    let context = format!("{name}, {input:?} in chunks of {size}, {options:?}");
+                    assert_eq!(out, *expected, "{context}");
+                    if let Some(stats) = stats {
+                        assert_eq!(totals(stats), expected_stats, "{context}");
+                    }
+                }
+            }
+        }
+    }
+}
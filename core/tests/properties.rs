@@ -0,0 +1,90 @@
+//! Property tests comparing the streaming functions against trimming the
+//! whole input at once, for arbitrary inputs split into arbitrary chunks.
+
+use std::io::BufReader;
+use std::io::Write;
+
+use nln_core::Options;
+use nln_core::StripTrailingNewlines;
+use proptest::collection::vec;
+use proptest::prelude::*;
+
+/// What stripping trailing newlines means, on the whole input at once.
+fn trim_end(input: &[u8]) -> &[u8] {
+    let end = input
+        .iter()
+        .rposition(|&b| b != b'\r' && b != b'\n')
+        .map_or(0, |i| i + 1);
+    &input[..end]
+}
+
+/// Bytes where newlines, and runs of them, are common.
+fn input() -> impl Strategy<Value = Vec<u8>> {
+    vec(prop_oneof![Just(b'\n'), Just(b'\r'), any::<u8>()], 0..256)
+}
+
+/// Splits `input` into chunks of the given lengths, the last one taking
+/// what is left.
+fn split<'a>(input: &'a [u8], lengths: &[usize]) -> Vec<&'a [u8]> {
+    let mut chunks = Vec::new();
+    let mut rest = input;
+    for &len in lengths {
+        let (chunk, tail) = rest.split_at(len.min(rest.len()));
+        chunks.push(chunk);
+        rest = tail;
+    }
+    chunks.push(rest);
+    chunks
+}
+
+proptest! {
+    #[test]
+    fn test_chunks_match_trim_end(input in input(), lengths in vec(0..16usize, 0..32)) {
+        let chunks = split(&input, &lengths).into_iter().map(Ok);
+        let mut out = Vec::new();
+        nln_core::process_chunks(chunks, &mut out, &Options::default()).unwrap();
+        prop_assert_eq!(out, trim_end(&input));
+    }
+
+    #[test]
+    fn test_reader_matches_trim_end(input in input(), capacity in 1..64usize) {
+        let reader = BufReader::with_capacity(capacity, input.as_slice());
+        let mut out = Vec::new();
+        nln_core::strip_trailing_newlines(reader, &mut out).unwrap();
+        prop_assert_eq!(out, trim_end(&input));
+    }
+
+    #[test]
+    fn test_stripper_matches_trim_end(input in input(), lengths in vec(0..16usize, 0..32)) {
+        let mut stripper = nln_core::Stripper::new(Options::default());
+        let mut out = Vec::new();
+        for chunk in split(&input, &lengths) {
+            stripper.push(chunk).unwrap().for_each(|piece| out.extend_from_slice(piece));
+        }
+        stripper.finish().for_each(|piece| out.extend_from_slice(piece));
+        prop_assert_eq!(out, trim_end(&input));
+    }
+
+    #[test]
+    fn test_writer_matches_snickerdoodle(
+        input in input(),
+        writes in vec((0..16usize, any::<bool>()), 0..32),
+    ) {
+        let mut expected = Vec::new();
+        #[allow(deprecated)]
+        nln_core::snickerdoodle(input.as_slice(), &mut expected).unwrap();
+
+        let lengths: Vec<usize> = writes.iter().map(|&(len, _)| len).collect();
+        let flushes = writes.iter().map(|&(_, flush)| flush).chain([false]);
+        let mut writer = StripTrailingNewlines::new(Vec::new());
+        for (chunk, flush) in split(&input, &lengths).into_iter().zip(flushes) {
+            writer.write_all(chunk).unwrap();
+            if flush {
+                writer.flush().unwrap();
+                // flushing may only pass on what is sure to stay
+                prop_assert!(expected.starts_with(writer.get_ref()));
+            }
+        }
+        prop_assert_eq!(writer.finish().unwrap(), expected);
+    }
+}
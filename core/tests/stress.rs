@@ -0,0 +1,185 @@
+//! Feeds pathological streams of newlines through nln and checks that its
+//! memory use stays bounded however long they are.
+//!
+//! Each stream is 4 MiB by default, which debug builds get through quickly.
+//! Set `NLN_STRESS_BYTES` for longer ones, such as with `mise run stress`,
+//! which runs several GiB in release mode.
+
+use std::alloc::GlobalAlloc;
+use std::alloc::Layout;
+use std::alloc::System;
+use std::cell::Cell;
+use std::env;
+use std::io;
+use std::io::Read;
+use std::io::Write;
+
+use nln_core::Error;
+use nln_core::Options;
+
+thread_local! {
+    /// Bytes allocated by this thread and not yet freed.
+    static CURRENT: Cell<isize> = const { Cell::new(0) };
+    /// The most `CURRENT` has been since [`peak_heap`] started.
+    static PEAK: Cell<isize> = const { Cell::new(0) };
+}
+
+/// Tracks the heap use of each thread, so tests running in parallel don't
+/// see each other's.
+struct Tracking;
+
+fn track(delta: isize) {
+    let current = CURRENT.with(|current| {
+        current.set(current.get() + delta);
+        current.get()
+    });
+    PEAK.with(|peak| peak.set(peak.get().max(current)));
+}
+
+unsafe impl GlobalAlloc for Tracking {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        track(layout.size() as isize);
+        // SAFETY: passed on as is
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        track(-(layout.size() as isize));
+        // SAFETY: passed on as is
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        track(new_size as isize - layout.size() as isize);
+        // SAFETY: passed on as is
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: Tracking = Tracking;
+
+/// The most a stream may make nln allocate, however long it is: the 64 KiB
+/// read buffer and a little for terminators and their output.
+const MAX_HEAP: isize = 256 * 1024;
+
+/// Runs `f`, returning its result and the most it had allocated at once.
+fn peak_heap<T>(f: impl FnOnce() -> T) -> (T, isize) {
+    let start = CURRENT.with(Cell::get);
+    PEAK.with(|peak| peak.set(start));
+    let result = f();
+    (result, PEAK.with(Cell::get) - start)
+}
+
+fn stream_len() -> u64 {
+    env::var("NLN_STRESS_BYTES").map_or(4 << 20, |len| {
+        len.parse().expect("NLN_STRESS_BYTES is a number of bytes")
+    })
+}
+
+/// Generates `pattern` over and over until `len` bytes have been read.
+struct Repeat {
+    /// `pattern` repeated to a few KiB, to copy from.
+    block: Vec<u8>,
+    offset: usize,
+    len: u64,
+}
+
+impl Repeat {
+    fn new(pattern: &[u8], len: u64) -> Self {
+        Self {
+            block: pattern.repeat(4096 / pattern.len()),
+            offset: 0,
+            len,
+        }
+    }
+}
+
+impl Read for Repeat {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut n = 0;
+        while n < buf.len() && self.len > 0 {
+            let available = &self.block[self.offset..];
+            let len = available
+                .len()
+                .min(buf.len() - n)
+                .min(usize::try_from(self.len).unwrap_or(usize::MAX));
+            buf[n..n + len].copy_from_slice(&available[..len]);
+            self.offset = (self.offset + len) % self.block.len();
+            self.len -= len as u64;
+            n += len;
+        }
+        Ok(n)
+    }
+}
+
+/// Counts what is written to it and keeps the last few bytes.
+#[derive(Default)]
+struct Tail {
+    len: u64,
+    last: Vec<u8>,
+}
+
+impl Tail {
+    const KEEP: usize = 16;
+}
+
+impl Write for Tail {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.len += buf.len() as u64;
+        self.last
+            .extend_from_slice(&buf[buf.len().saturating_sub(Self::KEEP)..]);
+        let excess = self.last.len().saturating_sub(Self::KEEP);
+        self.last.drain(..excess);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn test_only_newlines() {
+    let len = stream_len();
+    for pattern in [&b"\n"[..], b"\r\n", b"\r", b"\n\r"] {
+        for options in [
+            Options::default(),
+            Options::builder().keep_trailing(3).squeeze(true).build(),
+        ] {
+            let mut output = Tail::default();
+            let (stats, heap) = peak_heap(|| {
+                nln_core::process_read(Repeat::new(pattern, len), &mut output, &options).unwrap()
+            });
+            assert_eq!(stats.bytes_in, len);
+            assert!(output.len <= 6, "{pattern:?} {options:?}");
+            assert!(heap < MAX_HEAP, "{pattern:?} {options:?}: {heap} bytes");
+        }
+    }
+}
+
+#[test]
+fn test_newlines_between_content() {
+    let len = stream_len();
+    let options = Options::default();
+    let mut output = Tail::default();
+    // all of the newlines have to be output once the content after them
+    // arrives
+    let input = b"a".chain(Repeat::new(b"\r\n", len)).chain(b"b".as_slice());
+    let (stats, heap) = peak_heap(|| nln_core::process_read(input, &mut output, &options).unwrap());
+    assert_eq!(stats.bytes_out, len + 2);
+    assert_eq!(output.len, len + 2);
+    assert_eq!(output.last, b"\n\r\n\r\n\r\n\r\n\r\n\r\n\r\nb");
+    assert!(heap < MAX_HEAP, "{heap} bytes");
+}
+
+#[test]
+fn test_alternating_terminators_hit_the_limit() {
+    // every `\r\n` and `\n` is a run of its own, so without a limit the runs
+    // would take memory in proportion to the input
+    let options = Options::builder().max_pending_bytes(64 << 10).build();
+    let input = Repeat::new(b"\r\n\n", stream_len());
+    let (result, heap) = peak_heap(|| nln_core::process_read(input, &mut io::sink(), &options));
+    assert!(matches!(result, Err(Error::Limit(_))));
+    assert!(heap < 16 * MAX_HEAP, "{heap} bytes");
+}
@@ -0,0 +1,158 @@
+//! Times nln against equivalent one-liners and a plain copy on the same
+//! inputs, printing a markdown table of median times.
+//!
+//! ```sh
+//! cargo build --release
+//! cargo run --release --example compare -- [--runs N] [--readme] [FILE]...
+//! ```
+//!
+//! The files default to those in `testdata/`, which `mise run testdata`
+//! generates. With `--readme`, the table in README.md between the
+//! `compare:start` and `compare:end` markers is replaced.
+
+use std::env;
+use std::fs;
+use std::fs::File;
+use std::io;
+use std::path::Path;
+use std::path::PathBuf;
+use std::process::Command;
+use std::process::Stdio;
+use std::time::Duration;
+use std::time::Instant;
+
+const START_MARKER: &str = "<!-- compare:start -->";
+const END_MARKER: &str = "<!-- compare:end -->";
+
+/// The contenders, as shell commands reading stdin and writing stdout.
+const TOOLS: [(&str, &str); 5] = [
+    ("nln", "target/release/nln"),
+    ("perl", r"perl -0777 -pe 's/[\r\n]+\z//'"),
+    ("sed", r"sed -z 's/[\r\n]*$//'"),
+    (
+        "awk",
+        r#"awk 'length { if (s) printf "\n"; while (n) { printf "\n"; n-- } printf "%s", $0; s = 1; next } { n++ }'"#,
+    ),
+    ("shell", r#"printf '%s' "$(cat)""#),
+];
+
+fn main() -> io::Result<()> {
+    let mut runs = 5;
+    let mut readme = false;
+    let mut files = Vec::new();
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--runs" => {
+                runs = args
+                    .next()
+                    .and_then(|runs| runs.parse().ok())
+                    .filter(|&runs| runs > 0)
+                    .expect("--runs takes a positive number");
+            }
+            "--readme" => readme = true,
+            _ => files.push(PathBuf::from(arg)),
+        }
+    }
+    if files.is_empty() {
+        files = fs::read_dir("testdata")?
+            .filter_map(|entry| Some(entry.ok()?.path()))
+            .filter(|path| path.extension().is_some_and(|extension| extension == "txt"))
+            .collect();
+        files.sort();
+    }
+    if !Path::new(TOOLS[0].1).exists() {
+        eprintln!(
+            "{} not found: run `cargo build --release` first",
+            TOOLS[0].1
+        );
+        std::process::exit(1);
+    }
+
+    let mut table = String::from("| Input | Size |");
+    for (name, _) in TOOLS {
+        table.push_str(&format!(" {name} |"));
+    }
+    table.push_str(" io::copy |\n|---|---:|");
+    table.push_str(&"---:|".repeat(TOOLS.len() + 1));
+    table.push('\n');
+    for path in &files {
+        eprintln!("timing {}", path.display());
+        let size = fs::metadata(path)?.len();
+        table.push_str(&format!(
+            "| {} | {} |",
+            path.file_stem().unwrap_or_default().to_string_lossy(),
+            format_size(size)
+        ));
+        for (_, command) in TOOLS {
+            let time = median(runs, || time_command(command, path))?;
+            table.push_str(&format!(" {} |", format_duration(time)));
+        }
+        let time = median(runs, || time_copy(path))?;
+        table.push_str(&format!(" {} |\n", format_duration(time)));
+    }
+
+    print!("{table}");
+    if readme {
+        update_readme(&table)?;
+    }
+    Ok(())
+}
+
+/// Runs `command` with `input` on stdin and its output discarded.
+fn time_command(command: &str, input: &Path) -> io::Result<Duration> {
+    let input = File::open(input)?;
+    let start = Instant::now();
+    let status = Command::new("sh")
+        .args(["-c", command])
+        .stdin(input)
+        .stdout(Stdio::null())
+        .status()?;
+    let elapsed = start.elapsed();
+    if !status.success() {
+        return Err(io::Error::other(format!("{command:?} failed: {status}")));
+    }
+    Ok(elapsed)
+}
+
+/// Copies `input` to /dev/null with `io::copy`, the fastest any tool can be.
+fn time_copy(input: &Path) -> io::Result<Duration> {
+    let start = Instant::now();
+    io::copy(&mut File::open(input)?, &mut File::create("/dev/null")?)?;
+    Ok(start.elapsed())
+}
+
+fn median(runs: usize, mut time: impl FnMut() -> io::Result<Duration>) -> io::Result<Duration> {
+    let mut times = (0..runs).map(|_| time()).collect::<io::Result<Vec<_>>>()?;
+    times.sort();
+    Ok(times[runs / 2])
+}
+
+fn format_size(bytes: u64) -> String {
+    if bytes >= 1_000_000_000 {
+        format!("{:.1} GB", bytes as f64 / 1e9)
+    } else if bytes >= 1_000_000 {
+        format!("{:.1} MB", bytes as f64 / 1e6)
+    } else if bytes >= 1_000 {
+        format!("{:.1} kB", bytes as f64 / 1e3)
+    } else {
+        format!("{bytes} B")
+    }
+}
+
+fn format_duration(duration: Duration) -> String {
+    format!("{:.2} ms", duration.as_secs_f64() * 1e3)
+}
+
+fn update_readme(table: &str) -> io::Result<()> {
+    let readme = fs::read_to_string("README.md")?;
+    let (Some(start), Some(end)) = (readme.find(START_MARKER), readme.find(END_MARKER)) else {
+        return Err(io::Error::other("README.md has no compare markers"));
+    };
+    let updated = format!(
+        "{}{START_MARKER}\n{table}{}",
+        &readme[..start],
+        &readme[end..]
+    );
+    fs::write("README.md", updated)
+}
@@ -0,0 +1,50 @@
+/* C bindings for nln: remove trailing newlines and carriage returns. */
+
+#ifndef NLN_H
+#define NLN_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Return values. */
+#define NLN_OK 0
+/* The write callback returned nonzero. */
+#define NLN_ERR_WRITE (-1)
+/* A required pointer was NULL. */
+#define NLN_ERR_NULL (-2)
+
+/* Receives a piece of output. Returns 0 on success; anything else aborts
+ * processing with NLN_ERR_WRITE. */
+typedef int (*nln_write_cb)(const uint8_t *data, size_t len, void *user_data);
+
+/* Strips the trailing newlines from `len` bytes at `data`, passing the output
+ * to `write` in pieces. `data` may be NULL if `len` is 0. */
+int nln_strip(const uint8_t *data, size_t len, nln_write_cb write, void *user_data);
+
+/* A stripper for input arriving in chunks. */
+typedef struct nln_stripper nln_stripper;
+
+/* Creates a stripper with the default options. Free it with
+ * nln_stripper_free. */
+nln_stripper *nln_stripper_new(void);
+
+/* Processes the next chunk of input, passing the output known so far to
+ * `write`. */
+int nln_stripper_push(nln_stripper *stripper, const uint8_t *data, size_t len,
+                      nln_write_cb write, void *user_data);
+
+/* Signals the end of input, passing the remaining output to `write`. */
+int nln_stripper_finish(nln_stripper *stripper, nln_write_cb write, void *user_data);
+
+/* Frees a stripper. Does nothing if `stripper` is NULL. */
+void nln_stripper_free(nln_stripper *stripper);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
@@ -0,0 +1,240 @@
+//! C bindings for [`nln_core`], declared in `include/nln.h`.
+//!
+//! Everything is a thin layer over [`nln_core::Stripper`], so C callers get
+//! exactly the semantics of the Rust library.
+
+use std::ffi::c_int;
+use std::ffi::c_void;
+use std::slice;
+
+use nln_core::Options;
+use nln_core::Stripper;
+
+pub const NLN_OK: c_int = 0;
+pub const NLN_ERR_WRITE: c_int = -1;
+pub const NLN_ERR_NULL: c_int = -2;
+
+/// Receives a piece of output, returning 0 on success.
+pub type NlnWriteCb =
+    Option<unsafe extern "C" fn(data: *const u8, len: usize, user_data: *mut c_void) -> c_int>;
+
+/// A stripper for input arriving in chunks, opaque to C.
+pub struct NlnStripper(Stripper);
+
+/// Strips the trailing newlines from `len` bytes at `data`, passing the
+/// output to `write` in pieces.
+///
+/// # Safety
+///
+/// `data` must point to `len` readable bytes, or may be null if `len` is 0.
+/// `write` must be safe to call with `user_data`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn nln_strip(
+    data: *const u8,
+    len: usize,
+    write: NlnWriteCb,
+    user_data: *mut c_void,
+) -> c_int {
+    let mut stripper = NlnStripper(Stripper::new(Options::default()));
+    // SAFETY: the caller upholds the requirements of both functions
+    unsafe {
+        match nln_stripper_push(&mut stripper, data, len, write, user_data) {
+            NLN_OK => nln_stripper_finish(&mut stripper, write, user_data),
+            err => err,
+        }
+    }
+}
+
+/// Creates a stripper with the default options.
+#[unsafe(no_mangle)]
+pub extern "C" fn nln_stripper_new() -> *mut NlnStripper {
+    Box::into_raw(Box::new(NlnStripper(Stripper::new(Options::default()))))
+}
+
+/// Processes the next chunk of input, passing the output known so far to
+/// `write`.
+///
+/// # Safety
+///
+/// `stripper` must come from [`nln_stripper_new`] and not be freed. `data`
+/// must point to `len` readable bytes, or may be null if `len` is 0. `write`
+/// must be safe to call with `user_data`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn nln_stripper_push(
+    stripper: *mut NlnStripper,
+    data: *const u8,
+    len: usize,
+    write: NlnWriteCb,
+    user_data: *mut c_void,
+) -> c_int {
+    // SAFETY: guaranteed by the caller
+    let (Some(stripper), Some(write)) = (unsafe { stripper.as_mut() }, write) else {
+        return NLN_ERR_NULL;
+    };
+    let chunk = match (data.is_null(), len) {
+        (true, 0) => &[][..],
+        (true, _) => return NLN_ERR_NULL,
+        // SAFETY: guaranteed by the caller
+        (false, _) => unsafe { slice::from_raw_parts(data, len) },
+    };
+    let pieces = stripper
+        .0
+        .push(chunk)
+        .expect("the default options have no limit or cancel flag");
+    // SAFETY: guaranteed by the caller
+    unsafe { write_pieces(pieces, write, user_data) }
+}
+
+/// Signals the end of input, passing the remaining output to `write`.
+///
+/// # Safety
+///
+/// `stripper` must come from [`nln_stripper_new`] and not be freed. `write`
+/// must be safe to call with `user_data`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn nln_stripper_finish(
+    stripper: *mut NlnStripper,
+    write: NlnWriteCb,
+    user_data: *mut c_void,
+) -> c_int {
+    // SAFETY: guaranteed by the caller
+    let (Some(stripper), Some(write)) = (unsafe { stripper.as_mut() }, write) else {
+        return NLN_ERR_NULL;
+    };
+    // SAFETY: guaranteed by the caller
+    unsafe { write_pieces(stripper.0.finish(), write, user_data) }
+}
+
+/// Frees a stripper. Does nothing if `stripper` is null.
+///
+/// # Safety
+///
+/// `stripper` must be null or come from [`nln_stripper_new`] and not be freed
+/// already.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn nln_stripper_free(stripper: *mut NlnStripper) {
+    if !stripper.is_null() {
+        // SAFETY: guaranteed by the caller
+        drop(unsafe { Box::from_raw(stripper) });
+    }
+}
+
+/// # Safety
+///
+/// `write` must be safe to call with `user_data`.
+unsafe fn write_pieces<'a>(
+    pieces: impl Iterator<Item = &'a [u8]>,
+    write: unsafe extern "C" fn(*const u8, usize, *mut c_void) -> c_int,
+    user_data: *mut c_void,
+) -> c_int {
+    for piece in pieces {
+        // SAFETY: guaranteed by the caller
+        if unsafe { write(piece.as_ptr(), piece.len(), user_data) } != 0 {
+            return NLN_ERR_WRITE;
+        }
+    }
+    NLN_OK
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::c_int;
+    use std::ffi::c_void;
+    use std::ptr;
+    use std::slice;
+
+    use super::NLN_ERR_NULL;
+    use super::NLN_ERR_WRITE;
+    use super::NLN_OK;
+    use super::nln_strip;
+    use super::nln_stripper_finish;
+    use super::nln_stripper_free;
+    use super::nln_stripper_new;
+    use super::nln_stripper_push;
+
+    unsafe extern "C" fn collect(data: *const u8, len: usize, user_data: *mut c_void) -> c_int {
+        // SAFETY: the tests pass a `Vec<u8>` as user data
+        unsafe {
+            let out = &mut *user_data.cast::<Vec<u8>>();
+            out.extend_from_slice(slice::from_raw_parts(data, len));
+        }
+        0
+    }
+
+    unsafe extern "C" fn fail(_: *const u8, _: usize, _: *mut c_void) -> c_int {
+        1
+    }
+
+    #[test]
+    fn test_strip() {
+        let input = b"\na\r\n\r\n";
+        let mut out = Vec::<u8>::new();
+        let user_data = (&raw mut out).cast();
+        let status = unsafe { nln_strip(input.as_ptr(), input.len(), Some(collect), user_data) };
+        assert_eq!(status, NLN_OK);
+        assert_eq!(out, b"\na");
+
+        let status = unsafe { nln_strip(ptr::null(), 0, Some(collect), user_data) };
+        assert_eq!(status, NLN_OK);
+        let status = unsafe { nln_strip(input.as_ptr(), input.len(), Some(fail), user_data) };
+        assert_eq!(status, NLN_ERR_WRITE);
+        let status = unsafe { nln_strip(ptr::null(), 1, Some(collect), user_data) };
+        assert_eq!(status, NLN_ERR_NULL);
+    }
+
+    #[test]
+    fn test_stripper() {
+        let mut out = Vec::<u8>::new();
+        let user_data = (&raw mut out).cast();
+        let stripper = nln_stripper_new();
+        unsafe {
+            for chunk in [b"a\r".as_slice(), b"\nb", b"\n\n"] {
+                let status = nln_stripper_push(
+                    stripper,
+                    chunk.as_ptr(),
+                    chunk.len(),
+                    Some(collect),
+                    user_data,
+                );
+                assert_eq!(status, NLN_OK);
+            }
+            assert_eq!(
+                nln_stripper_finish(stripper, Some(collect), user_data),
+                NLN_OK
+            );
+            nln_stripper_free(stripper);
+            nln_stripper_free(ptr::null_mut());
+        }
+        assert_eq!(out, b"a\r\nb");
+    }
+
+    #[test]
+    fn test_stripper_without_callback() {
+        let mut out = Vec::<u8>::new();
+        let user_data = (&raw mut out).cast();
+        let stripper = nln_stripper_new();
+        unsafe {
+            // rejected before the stripper sees the chunk, so nothing is lost
+            let chunk = b"a\n\nb";
+            let status = nln_stripper_push(stripper, chunk.as_ptr(), chunk.len(), None, user_data);
+            assert_eq!(status, NLN_ERR_NULL);
+            assert_eq!(nln_stripper_finish(stripper, None, user_data), NLN_ERR_NULL);
+
+            let chunk = b"a\n";
+            let status = nln_stripper_push(
+                stripper,
+                chunk.as_ptr(),
+                chunk.len(),
+                Some(collect),
+                user_data,
+            );
+            assert_eq!(status, NLN_OK);
+            assert_eq!(
+                nln_stripper_finish(stripper, Some(collect), user_data),
+                NLN_OK
+            );
+            nln_stripper_free(stripper);
+        }
+        assert_eq!(out, b"a");
+    }
+}
@@ -0,0 +1,53 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use nln::Options;
+
+#[path = "../../src/args.rs"]
+mod args;
+
+fuzz_target!(|argv: Vec<String>| {
+    for arg in &argv {
+        let (name, inline) = args::split(arg);
+        assert!(arg.starts_with(name));
+        let value = inline.unwrap_or(arg);
+
+        // every value that parses has to be accepted by the builder
+        let mut options = Options::builder();
+        if let Ok(keep) = args::keep(value) {
+            options = options.keep_trailing(keep);
+        }
+        if let Ok(size) = args::buffer_size(value) {
+            options = options.buffer_size(size);
+        }
+        if let Ok(max) = args::max_pending_bytes(value) {
+            options = options.max_pending_bytes(max);
+        }
+        if let Ok(Some(line_ending)) = args::eol(value) {
+            options = options.line_ending(line_ending);
+        }
+        if let Ok(timeout) = args::timeout(value) {
+            assert!(!timeout.is_zero());
+        }
+        options.build();
+    }
+
+    // a whole command line either parses into a consistent configuration or
+    // is rejected with a message, without panicking
+    match args::parse(argv.into_iter()) {
+        Ok(config) if config.help || config.version => {}
+        Ok(config) if config.check => {
+            assert!(!config.files.is_empty());
+            assert!(!config.configured && !config.in_place && config.output.is_none());
+        }
+        Ok(config) if config.in_place => {
+            assert!(!config.files.is_empty());
+            assert!(config.output.is_none() && config.timeout.is_none());
+        }
+        Ok(config) => {
+            assert!(config.files.is_empty());
+            assert!(!(config.pipeline && config.eol_auto));
+        }
+        Err(message) => assert!(!message.is_empty()),
+    }
+});
@@ -0,0 +1,71 @@
+#![no_main]
+
+use std::io::BufRead;
+use std::io::Read;
+use std::io::Result;
+
+use libfuzzer_sys::fuzz_target;
+use nln_core::LineEnding;
+use nln_core::Options;
+
+/// Hands out `data` in chunks of the lengths in `lengths`, cycling through
+/// them, so newline runs and CRLFs get split at every possible place.
+struct Chunked<'a> {
+    data: &'a [u8],
+    lengths: &'a [u8],
+    /// The index of the next length to use.
+    next: usize,
+    /// The end of the current chunk.
+    end: usize,
+}
+
+impl Read for Chunked<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        let n = self.fill_buf()?.read(buf)?;
+        self.consume(n);
+        Ok(n)
+    }
+}
+
+impl BufRead for Chunked<'_> {
+    fn fill_buf(&mut self) -> Result<&[u8]> {
+        if self.end == 0 && !self.data.is_empty() {
+            let len = self
+                .lengths
+                .get(self.next % self.lengths.len().max(1))
+                .map_or(1, |&len| usize::from(len).max(1));
+            self.next += 1;
+            self.end = len.min(self.data.len());
+        }
+        Ok(&self.data[..self.end])
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.data = &self.data[amt..];
+        self.end -= amt;
+    }
+}
+
+fuzz_target!(|input: (Vec<u8>, Vec<u8>)| {
+    let (data, lengths) = input;
+    for options in [
+        Options::default(),
+        Options::builder().keep_trailing(1).squeeze(true).build(),
+        Options::builder()
+            .strip_leading(true)
+            .line_ending(LineEnding::CrLf)
+            .build(),
+    ] {
+        let mut whole = Vec::new();
+        nln_core::process(data.as_slice(), &mut whole, &options).unwrap();
+        let chunked = Chunked {
+            data: &data,
+            lengths: &lengths,
+            next: 0,
+            end: 0,
+        };
+        let mut out = Vec::new();
+        nln_core::process(chunked, &mut out, &options).unwrap();
+        assert_eq!(out, whole, "{options:?}");
+    }
+});
@@ -0,0 +1,22 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+/// Trivially correct: drop `\r` and `\n` bytes from the end one at a time.
+fn reference(data: &[u8]) -> &[u8] {
+    let mut end = data.len();
+    while end > 0 && matches!(data[end - 1], b'\r' | b'\n') {
+        end -= 1;
+    }
+    &data[..end]
+}
+
+fuzz_target!(|data: &[u8]| {
+    let expected = reference(data);
+
+    let mut out = Vec::new();
+    nln_core::strip_trailing_newlines(data, &mut out).unwrap();
+    assert_eq!(out, expected);
+
+    assert_eq!(nln_core::strip_trailing_newlines_bytes(data), expected);
+});
@@ -1,11 +1,11 @@
 #![no_main]
 
 use libfuzzer_sys::fuzz_target;
-use nln::snickerdoodle;
+use nln_core::strip_trailing_newlines;
 
 fuzz_target!(|data: &[u8]| {
     let mut bytes = Vec::new();
-    snickerdoodle(data, &mut bytes).unwrap();
+    strip_trailing_newlines(data, &mut bytes).unwrap();
     if let Some(&c) = bytes.last() {
         assert_ne!(c, b'\r');
         assert_ne!(c, b'\n');
@@ -0,0 +1,44 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use nln_core::CrLfPolicy;
+use nln_core::LfPolicy;
+use nln_core::LineEnding;
+use nln_core::Options;
+use nln_core::UnicodePolicy;
+
+/// Builds options from fuzzer-chosen bits, covering every mode and policy.
+fn options(flags: u8, keep: u8) -> Options {
+    let mut builder = Options::builder()
+        .strip_trailing(flags & 1 == 0)
+        .keep_trailing(usize::from(keep % 4))
+        .strip_leading(flags & 2 != 0)
+        .squeeze(flags & 4 != 0);
+    builder = match (flags >> 3) % 3 {
+        0 => builder,
+        1 => builder.line_ending(LineEnding::Lf),
+        _ => builder.line_ending(LineEnding::CrLf),
+    };
+    match (flags >> 5) % 4 {
+        0 => builder,
+        1 => builder.policy(LfPolicy),
+        2 => builder.policy(CrLfPolicy),
+        _ => builder.policy(UnicodePolicy),
+    }
+    .build()
+}
+
+fuzz_target!(|input: (u8, u8, Vec<u8>)| {
+    let (flags, keep, data) = input;
+    // where `\r` can be content, it joins up with the line ending written
+    // after it: with only `\n` terminating lines, a `\r\n` written in its
+    // place leaves a `\r` behind the next time round, and with only `\r\n`,
+    // content `\r` and an `\n` written after it become one
+    if matches!(((flags >> 3) % 3, (flags >> 5) % 4), (2, 1) | (1, 2)) {
+        return;
+    }
+    let options = options(flags, keep);
+    let once = nln_core::process_bytes(&data, &options);
+    let twice = nln_core::process_bytes(&once, &options);
+    assert_eq!(once, twice, "{options:?}");
+});
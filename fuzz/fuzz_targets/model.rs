@@ -0,0 +1,147 @@
+#![no_main]
+
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+use nln_core::CrLfPolicy;
+use nln_core::LfPolicy;
+use nln_core::LineEnding;
+use nln_core::Options;
+use nln_core::UnicodePolicy;
+
+#[derive(Debug, Clone, Copy, Arbitrary)]
+enum Policy {
+    Ascii,
+    Lf,
+    CrLf,
+    Unicode,
+}
+
+#[derive(Debug, Clone, Copy, Arbitrary)]
+enum Ending {
+    Lf,
+    CrLf,
+}
+
+/// Options, and input split into chunks.
+#[derive(Debug, Arbitrary)]
+struct Case {
+    strip_trailing: bool,
+    keep: u8,
+    strip_leading: bool,
+    squeeze: bool,
+    line_ending: Option<Ending>,
+    policy: Policy,
+    chunks: Vec<Vec<u8>>,
+}
+
+impl Case {
+    fn options(&self) -> Options {
+        let mut builder = Options::builder()
+            .strip_trailing(self.strip_trailing)
+            .keep_trailing(usize::from(self.keep))
+            .strip_leading(self.strip_leading)
+            .squeeze(self.squeeze);
+        builder = match self.line_ending {
+            None => builder,
+            Some(Ending::Lf) => builder.line_ending(LineEnding::Lf),
+            Some(Ending::CrLf) => builder.line_ending(LineEnding::CrLf),
+        };
+        match self.policy {
+            Policy::Ascii => builder,
+            Policy::Lf => builder.policy(LfPolicy),
+            Policy::CrLf => builder.policy(CrLfPolicy),
+            Policy::Unicode => builder.policy(UnicodePolicy),
+        }
+        .build()
+    }
+
+    /// The length of the terminator `bytes` starts with, or 0 for content.
+    fn terminator_len(&self, bytes: &[u8]) -> usize {
+        let ascii = if bytes.starts_with(b"\r\n") {
+            2
+        } else {
+            usize::from(matches!(bytes[0], b'\n' | b'\r'))
+        };
+        match self.policy {
+            Policy::Ascii => ascii,
+            Policy::Lf => usize::from(bytes[0] == b'\n'),
+            Policy::CrLf if bytes.starts_with(b"\r\n") => 2,
+            Policy::CrLf => 0,
+            Policy::Unicode if bytes.starts_with(b"\xC2\x85") => 2,
+            Policy::Unicode
+                if bytes.starts_with(b"\xE2\x80\xA8") || bytes.starts_with(b"\xE2\x80\xA9") =>
+            {
+                3
+            }
+            Policy::Unicode => ascii,
+        }
+    }
+
+    /// How many of a run of `len` terminators are output.
+    fn kept(&self, len: usize, leading: bool, trailing: bool) -> usize {
+        let mut kept = len;
+        if leading && self.strip_leading {
+            kept = 0;
+        }
+        if self.squeeze {
+            kept = kept.min(if leading { 1 } else { 2 });
+        }
+        if trailing && self.strip_trailing {
+            kept = kept.min(usize::from(self.keep));
+        }
+        kept
+    }
+
+    /// Writes the first `kept` terminators of `run`.
+    fn write_run(&self, out: &mut Vec<u8>, run: &[&[u8]], kept: usize) {
+        for terminator in &run[..kept] {
+            out.extend_from_slice(match self.line_ending {
+                None => terminator,
+                Some(Ending::Lf) => b"\n",
+                Some(Ending::CrLf) => b"\r\n",
+            });
+        }
+    }
+
+    /// The expected output for `data`, worked out the obvious way: over the
+    /// whole input at once, one terminator at a time.
+    fn model(&self, data: &[u8]) -> Vec<u8> {
+        // each run of terminators with the content after it
+        let mut lines: Vec<(Vec<&[u8]>, Vec<u8>)> = Vec::new();
+        let mut run = Vec::new();
+        let mut i = 0;
+        while i < data.len() {
+            let len = self.terminator_len(&data[i..]);
+            if len > 0 {
+                run.push(&data[i..i + len]);
+                i += len;
+                continue;
+            }
+            match lines.last_mut() {
+                Some((_, content)) if run.is_empty() => content.push(data[i]),
+                _ => lines.push((std::mem::take(&mut run), vec![data[i]])),
+            }
+            i += 1;
+        }
+
+        let mut out = Vec::new();
+        for (i, (run, content)) in lines.iter().enumerate() {
+            self.write_run(&mut out, run, self.kept(run.len(), i == 0, false));
+            out.extend_from_slice(content);
+        }
+        self.write_run(&mut out, &run, self.kept(run.len(), lines.is_empty(), true));
+        out
+    }
+}
+
+fuzz_target!(|case: Case| {
+    let options = case.options();
+    let data = case.chunks.concat();
+    let expected = case.model(&data);
+
+    assert_eq!(*nln_core::process_bytes(&data, &options), *expected);
+
+    let mut out = Vec::new();
+    nln_core::process_chunks(case.chunks.iter().map(Ok), &mut out, &options).unwrap();
+    assert_eq!(out, expected);
+});
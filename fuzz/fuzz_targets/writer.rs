@@ -0,0 +1,42 @@
+#![no_main]
+
+use std::io::Write;
+
+use libfuzzer_sys::fuzz_target;
+use nln_core::LineEnding;
+use nln_core::Options;
+use nln_core::StripTrailingNewlines;
+
+fuzz_target!(|input: (Vec<u8>, Vec<u8>)| {
+    let (data, splits) = input;
+    for options in [
+        Options::default(),
+        Options::builder().keep_trailing(1).squeeze(true).build(),
+        Options::builder()
+            .strip_leading(true)
+            .line_ending(LineEnding::CrLf)
+            .build(),
+    ] {
+        let expected = nln_core::process_bytes(&data, &options);
+
+        // each split writes up to its low bits' worth of bytes, at least
+        // one, and flushes first if its high bit is set
+        let mut writer = StripTrailingNewlines::with_options(Vec::new(), options.clone());
+        let mut rest = data.as_slice();
+        for &split in splits.iter().cycle() {
+            if rest.is_empty() {
+                break;
+            }
+            if split & 0x80 != 0 {
+                writer.flush().unwrap();
+            }
+            let len = usize::from(split & 0x7f).clamp(1, rest.len());
+            let written = writer.write(&rest[..len]).unwrap();
+            assert!(written <= len);
+            rest = &rest[written..];
+        }
+        writer.write_all(rest).unwrap();
+        let out = writer.finish().unwrap();
+        assert_eq!(out, *expected, "{options:?}");
+    }
+});
@@ -0,0 +1,26 @@
+'use strict';
+
+const { Transform } = require('node:stream');
+const native = require('./nln.node');
+
+/** A Transform stream that strips trailing newlines from what passes through. */
+class StripTrailingNewlines extends Transform {
+  constructor(options) {
+    super(options);
+    this.stripper = new native.Stripper();
+  }
+
+  _transform(chunk, encoding, callback) {
+    callback(null, this.stripper.push(Buffer.from(chunk, encoding)));
+  }
+
+  _flush(callback) {
+    callback(null, this.stripper.finish());
+  }
+}
+
+module.exports = {
+  stripTrailingNewlines: native.stripTrailingNewlines,
+  Stripper: native.Stripper,
+  StripTrailingNewlines,
+};
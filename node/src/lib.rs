@@ -0,0 +1,52 @@
+//! Node.js bindings for [`nln_core`] through napi-rs. `index.js` wraps
+//! [`Stripper`] in a `Transform` stream.
+
+use napi::bindgen_prelude::Buffer;
+use napi_derive::napi;
+use nln_core::Options;
+
+/// Returns `input` without its trailing newlines.
+#[napi]
+pub fn strip_trailing_newlines(input: Buffer) -> Buffer {
+    nln_core::strip_trailing_newlines_bytes(&input)
+        .to_vec()
+        .into()
+}
+
+/// A stripper for input arriving in chunks.
+#[napi]
+pub struct Stripper(nln_core::Stripper);
+
+#[napi]
+impl Stripper {
+    #[napi(constructor)]
+    pub fn new() -> Self {
+        Self(nln_core::Stripper::new(Options::default()))
+    }
+
+    /// Processes the next chunk of input, returning the output known so far.
+    #[napi]
+    pub fn push(&mut self, chunk: Buffer) -> Buffer {
+        let output: Vec<u8> = self
+            .0
+            .push(&chunk)
+            .expect("the default options have no limit or cancel flag")
+            .flatten()
+            .copied()
+            .collect();
+        output.into()
+    }
+
+    /// Signals the end of input, returning the remaining output.
+    #[napi]
+    pub fn finish(&mut self) -> Buffer {
+        let output: Vec<u8> = self.0.finish().flatten().copied().collect();
+        output.into()
+    }
+}
+
+impl Default for Stripper {
+    fn default() -> Self {
+        Self::new()
+    }
+}
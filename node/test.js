@@ -0,0 +1,27 @@
+'use strict';
+
+const assert = require('node:assert');
+const { Readable } = require('node:stream');
+const { test } = require('node:test');
+
+const { stripTrailingNewlines, Stripper, StripTrailingNewlines } = require('./index.js');
+
+test('stripTrailingNewlines', () => {
+  assert.deepStrictEqual(stripTrailingNewlines(Buffer.from('\na\r\n\n')), Buffer.from('\na'));
+});
+
+test('Stripper holds back newlines split across chunks', () => {
+  const stripper = new Stripper();
+  const output = ['a\r', '\nb\r', '\r\n\n'].map((chunk) => stripper.push(Buffer.from(chunk)));
+  output.push(stripper.finish());
+  assert.deepStrictEqual(Buffer.concat(output), Buffer.from('a\r\nb'));
+});
+
+test('StripTrailingNewlines', async () => {
+  const output = [];
+  const stream = Readable.from(['a\n', '\nb\n', '\n']).pipe(new StripTrailingNewlines());
+  for await (const chunk of stream) {
+    output.push(chunk);
+  }
+  assert.deepStrictEqual(Buffer.concat(output), Buffer.from('a\n\nb'));
+});
@@ -0,0 +1,487 @@
+//! Parsing the command line into a [`Config`]. This only depends on the
+//! library, so the fuzz target can include it as is.
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use nln::Flush;
+use nln::LineEnding;
+use nln::Options;
+use nln::UnicodePolicy;
+
+/// Everything the command line asks for, checked for conflicts.
+#[derive(Debug)]
+pub struct Config {
+    /// `--help` was given, and nothing after it was parsed.
+    pub help: bool,
+    /// `--version` was given, and nothing after it was parsed.
+    pub version: bool,
+    pub report: bool,
+    pub check: bool,
+    pub format: Format,
+    pub git_filter: Option<GitFilter>,
+    pub timeout: Option<Duration>,
+    pub timeout_flush: bool,
+    pub broken_pipe_error: bool,
+    pub output: Option<PathBuf>,
+    pub copy: bool,
+    pub paste: bool,
+    pub osc52: bool,
+    /// `--tmux-buffer`, with the name of the buffer if one was given.
+    pub tmux_buffer: Option<Option<String>>,
+    pub in_place: bool,
+    pub fail_fast: bool,
+    pub baseline: Option<PathBuf>,
+    pub write_baseline: bool,
+    pub cache: Option<PathBuf>,
+    pub ensure_final_newline: bool,
+    pub summary: bool,
+    pub options: Options,
+    /// Any option that changes what is stripped was given.
+    pub configured: bool,
+    /// `--max-pending-bytes` was given.
+    pub limited: bool,
+    /// `--eol auto` was given, so the line ending depends on the input.
+    pub eol_auto: bool,
+    pub mmap: bool,
+    pub fadvise: bool,
+    pub pipeline: bool,
+    pub buffer_size: Option<usize>,
+    pub read_buffer: Option<usize>,
+    pub write_buffer: Option<usize>,
+    pub io_backend: IoBackend,
+    /// The FILE arguments, or `-` for stdin with `--check` and none given.
+    pub files: Vec<PathBuf>,
+}
+
+/// Output format for `--check`.
+#[derive(Debug, Clone, Copy)]
+pub enum Format {
+    Text,
+    Json,
+    Sarif,
+    /// reviewdog's rdjson diagnostic format.
+    Rdjson,
+    /// A JUnit XML report with one test case per input.
+    Junit,
+    /// Test Anything Protocol, one test point per input.
+    Tap,
+    /// Only the paths of inputs with trailing newlines, each terminated by a
+    /// newline or, if `null` is set, a NUL byte.
+    List {
+        null: bool,
+    },
+}
+
+/// Which side of a git filter driver nln is running as.
+///
+/// Clean runs when content is staged and strips trailing newlines like the
+/// default mode. Smudge runs on checkout and leaves the working tree copy
+/// ending in exactly one newline.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GitFilter {
+    Clean,
+    Smudge,
+}
+
+/// How input is moved to output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IoBackend {
+    /// io_uring where it is available, standard I/O otherwise.
+    Auto,
+    /// Blocking reads and writes.
+    Std,
+    /// io_uring, failing if it isn't available.
+    Uring,
+}
+
+/// Parses the arguments after the program name, returning the message to
+/// print for a usage error.
+pub fn parse(mut args: impl Iterator<Item = String>) -> Result<Config, String> {
+    let mut config = Config {
+        help: false,
+        version: false,
+        report: false,
+        check: false,
+        format: Format::Text,
+        git_filter: None,
+        timeout: None,
+        timeout_flush: false,
+        broken_pipe_error: false,
+        output: None,
+        copy: false,
+        paste: false,
+        osc52: false,
+        tmux_buffer: None,
+        in_place: false,
+        fail_fast: false,
+        baseline: None,
+        write_baseline: false,
+        cache: None,
+        ensure_final_newline: false,
+        summary: false,
+        options: Options::default(),
+        configured: false,
+        limited: false,
+        eol_auto: false,
+        mmap: false,
+        fadvise: true,
+        pipeline: false,
+        buffer_size: None,
+        read_buffer: None,
+        write_buffer: None,
+        io_backend: IoBackend::Auto,
+        files: Vec::new(),
+    };
+    let mut options = Options::builder();
+    let mut print0 = false;
+    let mut keep_given = false;
+    let mut format_given = false;
+    let mut flush = None;
+    let mut no_final_flush = false;
+
+    while let Some(arg) = args.next() {
+        let (name, inline) = split(&arg);
+        match (name, inline) {
+            ("--help" | "-h", None) => {
+                config.help = true;
+                return Ok(config);
+            }
+            ("--version" | "-v", None) => {
+                config.version = true;
+                return Ok(config);
+            }
+            ("--report", None) => config.report = true,
+            ("--check", None) => config.check = true,
+            ("--list" | "-l", None) => {
+                config.check = true;
+                config.format = Format::List { null: false };
+            }
+            ("--print0" | "-0", None) => print0 = true,
+            ("--summary", None) => config.summary = true,
+            ("--mmap", None) => config.mmap = true,
+            ("--no-fadvise", None) => config.fadvise = false,
+            ("--pipeline", None) => config.pipeline = true,
+            ("--line-buffered", None) => flush = Some(Flush::Line),
+            ("--no-final-flush", None) => no_final_flush = true,
+            ("--io-backend", value) => {
+                config.io_backend = io_backend(&value_of(value, &mut args))?;
+            }
+            ("--fail-fast", None) => config.fail_fast = true,
+            ("--baseline", value) => {
+                config.baseline = Some(PathBuf::from(value_of(value, &mut args)));
+            }
+            ("--write-baseline", None) => config.write_baseline = true,
+            ("--cache", value) => config.cache = Some(PathBuf::from(value_of(value, &mut args))),
+            ("--ensure-final-newline", None) => config.ensure_final_newline = true,
+            ("--format", value) => {
+                config.format = format(&value_of(value, &mut args))?;
+                format_given = true;
+            }
+            ("--git-filter", value) => {
+                config.git_filter = Some(git_filter(&value_of(value, &mut args))?);
+            }
+            ("--timeout", value) => config.timeout = Some(timeout(&value_of(value, &mut args))?),
+            ("--timeout-flush", None) => config.timeout_flush = true,
+            ("--broken-pipe-error", None) => config.broken_pipe_error = true,
+            ("--output" | "-o", value) => {
+                config.output = Some(PathBuf::from(value_of(value, &mut args)));
+            }
+            ("--in-place" | "-i", None) => config.in_place = true,
+            ("--copy", None) => config.copy = true,
+            ("--paste", None) => config.paste = true,
+            ("--osc52", None) => config.osc52 = true,
+            ("--tmux-buffer", name) => config.tmux_buffer = Some(name.map(str::to_string)),
+            ("--keep", value) => {
+                options = options.keep_trailing(keep(&value_of(value, &mut args))?);
+                config.configured = true;
+                keep_given = true;
+            }
+            ("--strip-leading", None) => {
+                options = options.strip_leading(true);
+                config.configured = true;
+            }
+            ("--squeeze", None) => {
+                options = options.squeeze(true);
+                config.configured = true;
+            }
+            ("--eol", value) => {
+                match eol(&value_of(value, &mut args))? {
+                    Some(line_ending) => options = options.line_ending(line_ending),
+                    None => config.eol_auto = true,
+                }
+                config.configured = true;
+            }
+            ("--buffer-size", value) => {
+                config.buffer_size = Some(buffer_size(&value_of(value, &mut args))?);
+            }
+            ("--read-buffer", value) => {
+                config.read_buffer = Some(buffer_size(&value_of(value, &mut args))?);
+            }
+            ("--write-buffer", value) => {
+                config.write_buffer = Some(buffer_size(&value_of(value, &mut args))?);
+            }
+            ("--max-pending-bytes", value) => {
+                options =
+                    options.max_pending_bytes(max_pending_bytes(&value_of(value, &mut args))?);
+                config.limited = true;
+            }
+            ("--unicode", None) => {
+                options = options.policy(UnicodePolicy);
+                config.configured = true;
+            }
+            ("--", None) => config.files.extend(args.by_ref().map(PathBuf::from)),
+            _ if arg.starts_with('-') && arg != "-" => {
+                return Err(format!("Unknown argument: {arg}"));
+            }
+            _ => config.files.push(PathBuf::from(&arg)),
+        }
+    }
+
+    if print0 {
+        let Format::List { null } = &mut config.format else {
+            return Err("--print0 requires --list".to_string());
+        };
+        *null = true;
+    }
+
+    if config.timeout_flush && config.timeout.is_none() {
+        return Err("--timeout-flush requires --timeout".to_string());
+    }
+
+    if config.check {
+        if config.git_filter.is_some()
+            || config.timeout.is_some()
+            || config.output.is_some()
+            || config.in_place
+            || config.copy
+            || config.paste
+            || config.osc52
+            || config.tmux_buffer.is_some()
+            || config.configured
+        {
+            return Err(
+                "--git-filter, --timeout, --output, --in-place, --copy, --paste, \
+                        --osc52, --tmux-buffer and the stripping options cannot be used with \
+                        --check"
+                    .to_string(),
+            );
+        }
+        if config.files.is_empty() {
+            config.files.push(PathBuf::from("-"));
+        }
+        if config.write_baseline && config.baseline.is_none() {
+            return Err("--write-baseline requires --baseline".to_string());
+        }
+        return Ok(config);
+    }
+
+    if format_given
+        || config.fail_fast
+        || config.baseline.is_some()
+        || config.write_baseline
+        || config.cache.is_some()
+        || config.ensure_final_newline
+    {
+        return Err(
+            "--format, --fail-fast, --baseline, --write-baseline, --cache and \
+                    --ensure-final-newline require --check"
+                .to_string(),
+        );
+    }
+    // smudge leaves exactly one newline, so there are none to keep
+    if config.git_filter == Some(GitFilter::Smudge) && keep_given {
+        return Err("--keep cannot be used with --git-filter smudge".to_string());
+    }
+    if let Some(size) = config.buffer_size {
+        options = options.buffer_size(size);
+    }
+    if no_final_flush {
+        if flush.is_some() {
+            return Err("--line-buffered cannot be used with --no-final-flush".to_string());
+        }
+        flush = Some(Flush::Never);
+    }
+    if let Some(flush) = flush {
+        options = options.flush(flush);
+    }
+    config.options = options.build();
+
+    if config.in_place {
+        if config.files.is_empty()
+            || config.output.is_some()
+            || config.copy
+            || config.paste
+            || config.osc52
+            || config.tmux_buffer.is_some()
+            || config.report
+            || config.timeout.is_some()
+        {
+            return Err(
+                "--in-place requires FILE arguments and cannot be used with --output, --copy, \
+                 --paste, --osc52, --tmux-buffer, --report or --timeout"
+                    .to_string(),
+            );
+        }
+        return Ok(config);
+    }
+
+    if !config.files.is_empty() {
+        return Err("FILE arguments require --check or --in-place".to_string());
+    }
+    if config.summary {
+        return Err("--summary requires --check or --in-place".to_string());
+    }
+    if (config.copy || config.osc52 || config.tmux_buffer.is_some()) && config.output.is_some() {
+        return Err("--copy, --osc52 and --tmux-buffer cannot be used with --output".to_string());
+    }
+    if config.paste && (config.pipeline || config.timeout.is_some()) {
+        return Err("--paste cannot be used with --pipeline or --timeout".to_string());
+    }
+    if config.eol_auto && config.timeout.is_some() {
+        return Err("--eol auto cannot be used with --timeout".to_string());
+    }
+    if config.pipeline && (config.eol_auto || config.timeout.is_some()) {
+        return Err("--pipeline cannot be used with --eol auto or --timeout".to_string());
+    }
+    Ok(config)
+}
+
+/// Returns the value of an option, either given inline or as the next
+/// argument.
+fn value_of(inline: Option<&str>, args: &mut impl Iterator<Item = String>) -> String {
+    inline
+        .map(str::to_string)
+        .or_else(|| args.next())
+        .unwrap_or_default()
+}
+
+/// Splits a long option that carries its value inline, as in
+/// `--format=json`, into its name and value.
+pub fn split(arg: &str) -> (&str, Option<&str>) {
+    match arg.split_once('=') {
+        Some((name, value)) if name.starts_with("--") => (name, Some(value)),
+        _ => (arg, None),
+    }
+}
+
+pub fn keep(value: &str) -> Result<usize, String> {
+    value.parse().map_err(|_| {
+        format!("Invalid count for --keep: {value:?} (expected a non-negative integer)")
+    })
+}
+
+pub fn buffer_size(value: &str) -> Result<usize, String> {
+    match value.parse() {
+        Ok(size) if size > 0 => Ok(size),
+        _ => Err(format!(
+            "Invalid buffer size: {value:?} (expected a positive number of bytes)"
+        )),
+    }
+}
+
+pub fn max_pending_bytes(value: &str) -> Result<u64, String> {
+    value.parse().map_err(|_| {
+        format!("Invalid limit for --max-pending-bytes: {value:?} (expected a number of bytes)")
+    })
+}
+
+/// Parses the value of `--eol`, where `None` stands for `auto`.
+pub fn eol(value: &str) -> Result<Option<LineEnding>, String> {
+    match value {
+        "lf" => Ok(Some(LineEnding::Lf)),
+        "crlf" => Ok(Some(LineEnding::CrLf)),
+        "auto" => Ok(None),
+        _ => Err(format!(
+            "Unknown line ending: {value:?} (expected lf, crlf or auto)"
+        )),
+    }
+}
+
+pub fn timeout(value: &str) -> Result<Duration, String> {
+    match value.parse().map(Duration::try_from_secs_f64) {
+        Ok(Ok(timeout)) if !timeout.is_zero() => Ok(timeout),
+        _ => Err(format!(
+            "Invalid timeout: {value:?} (expected a positive number of seconds)"
+        )),
+    }
+}
+
+pub fn git_filter(value: &str) -> Result<GitFilter, String> {
+    match value {
+        "clean" => Ok(GitFilter::Clean),
+        "smudge" => Ok(GitFilter::Smudge),
+        _ => Err(format!(
+            "Unknown git filter: {value:?} (expected clean or smudge)"
+        )),
+    }
+}
+
+pub fn io_backend(value: &str) -> Result<IoBackend, String> {
+    match value {
+        "auto" => Ok(IoBackend::Auto),
+        "std" => Ok(IoBackend::Std),
+        "uring" => Ok(IoBackend::Uring),
+        _ => Err(format!(
+            "Unknown I/O backend: {value:?} (expected auto, std or uring)"
+        )),
+    }
+}
+
+pub fn format(value: &str) -> Result<Format, String> {
+    match value {
+        "text" => Ok(Format::Text),
+        "json" => Ok(Format::Json),
+        "sarif" => Ok(Format::Sarif),
+        "rdjson" => Ok(Format::Rdjson),
+        "junit" => Ok(Format::Junit),
+        "tap" => Ok(Format::Tap),
+        _ => Err(format!(
+            "Unknown format: {value:?} (expected text, json, sarif, rdjson, junit or tap)"
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_args(args: &[&str]) -> Result<Config, String> {
+        parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn test_parse() {
+        let config = parse_args(&["--keep=1", "--eol", "auto", "-o", "out.txt"]).unwrap();
+        assert!(config.configured && config.eol_auto);
+        assert_eq!(config.output, Some(PathBuf::from("out.txt")));
+
+        let config = parse_args(&["--list", "-0"]).unwrap();
+        assert!(matches!(config.format, Format::List { null: true }));
+        assert_eq!(config.files, [PathBuf::from("-")]);
+
+        assert!(parse_args(&["--help", "--bogus"]).unwrap().help);
+        assert!(parse_args(&["-i", "--", "--bogus"]).is_ok());
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let cases: [&[&str]; 12] = [
+            &["--bogus"],
+            &["--keep", "many"],
+            &["--print0"],
+            &["--check", "--squeeze"],
+            &["--git-filter", "smudge", "--keep", "1"],
+            &["--line-buffered", "--no-final-flush"],
+            &["--in-place"],
+            &["file.txt"],
+            &["--format", "json"],
+            &["--cache", "cache"],
+            &["--summary"],
+            &["--timeout-flush"],
+        ];
+        for args in cases {
+            assert!(parse_args(args).is_err(), "{args:?}");
+        }
+    }
+}
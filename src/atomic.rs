@@ -0,0 +1,165 @@
+//! Writing files through a temporary file that replaces the target on success.
+
+use std::fs;
+use std::fs::File;
+use std::fs::Permissions;
+use std::io::Result;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+#[cfg(any(unix, windows))]
+use std::process;
+use std::sync::Mutex;
+use std::sync::MutexGuard;
+use std::sync::Once;
+
+/// Temporary files that still need removing if the process is interrupted.
+static PENDING: Mutex<Vec<PathBuf>> = Mutex::new(Vec::new());
+
+/// Exit status for a process interrupted by Ctrl-C, as for SIGINT (128 + 2).
+#[cfg(windows)]
+const INTERRUPTED_STATUS: i32 = 130;
+
+/// An [`nln::AtomicFile`] whose temporary file is also removed if nln
+/// receives SIGINT, SIGTERM or SIGHUP while it is open.
+pub struct AtomicFile {
+    file: nln::AtomicFile,
+    /// Dropped after `file`, once the temporary file is gone.
+    _registered: Option<Registered>,
+}
+
+/// A temporary file registered for removal on interrupt, unregistered when
+/// dropped.
+struct Registered(PathBuf);
+
+impl Drop for Registered {
+    fn drop(&mut self) {
+        pending().retain(|temp| *temp != self.0);
+    }
+}
+
+impl AtomicFile {
+    pub fn create(target: &Path) -> Result<Self> {
+        static HANDLER: Once = Once::new();
+
+        HANDLER.call_once(install_interrupt_handler);
+
+        let file = nln::AtomicFile::create(target)?;
+        // an interrupt before this leaves the temporary file behind, as for
+        // any other user of the library
+        let registered = file.temp_path().map(|temp| {
+            pending().push(temp.to_path_buf());
+            Registered(temp.to_path_buf())
+        });
+        Ok(Self {
+            file,
+            _registered: registered,
+        })
+    }
+
+    /// The temporary file, or the target if it isn't a regular file, to write
+    /// to it directly.
+    pub fn file(&self) -> &File {
+        self.file.file()
+    }
+
+    pub fn set_permissions(&self, permissions: Permissions) -> Result<()> {
+        self.file.set_permissions(permissions)
+    }
+
+    /// Replaces the target with everything written so far.
+    pub fn commit(self) -> Result<()> {
+        self.file.commit()
+    }
+}
+
+impl Write for AtomicFile {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        self.file.write(buf)
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.file.flush()
+    }
+}
+
+/// Handles SIGINT, SIGTERM and SIGHUP by removing the pending temporary
+/// files on a thread of its own, then exiting with the status a shell
+/// reports for a process killed by the signal, such as 143 for SIGTERM.
+#[cfg(unix)]
+fn install_interrupt_handler() {
+    use std::io;
+    use std::io::Read;
+    use std::mem;
+    use std::os::fd::IntoRawFd;
+    use std::ptr;
+    use std::sync::atomic::AtomicI32;
+    use std::sync::atomic::Ordering;
+    use std::thread;
+
+    /// The last signal received.
+    static SIGNAL: AtomicI32 = AtomicI32::new(0);
+    /// The write end of the pipe that wakes the cleanup thread.
+    static WAKE: AtomicI32 = AtomicI32::new(-1);
+
+    extern "C" fn on_signal(signal: libc::c_int) {
+        SIGNAL.store(signal, Ordering::Relaxed);
+        // SAFETY: write(2) is async-signal-safe, and the byte outlives it
+        unsafe { libc::write(WAKE.load(Ordering::Relaxed), [0u8].as_ptr().cast(), 1) };
+    }
+
+    let Ok((mut wake, writer)) = io::pipe() else {
+        return;
+    };
+    let cleanup = thread::Builder::new().spawn(move || {
+        let _ = wake.read(&mut [0]);
+        remove_pending();
+        process::exit(128 + SIGNAL.load(Ordering::Relaxed));
+    });
+    if cleanup.is_err() {
+        return;
+    }
+    WAKE.store(writer.into_raw_fd(), Ordering::Relaxed);
+    let handler = on_signal as extern "C" fn(libc::c_int);
+    for signal in [libc::SIGINT, libc::SIGTERM, libc::SIGHUP] {
+        // SAFETY: the handler only does what is safe in a signal handler
+        unsafe {
+            let mut action: libc::sigaction = mem::zeroed();
+            action.sa_sigaction = handler as libc::sighandler_t;
+            action.sa_flags = libc::SA_RESTART;
+            libc::sigaction(signal, &action, ptr::null_mut());
+        }
+    }
+}
+
+#[cfg(windows)]
+fn install_interrupt_handler() {
+    // only fails if another handler is installed, which nln never does
+    let _ = ctrlc::set_handler(|| {
+        remove_pending();
+        process::exit(INTERRUPTED_STATUS);
+    });
+}
+
+/// WASI has no signals, so there is nothing to clean up after.
+#[cfg(not(any(unix, windows)))]
+fn install_interrupt_handler() {}
+
+fn pending() -> MutexGuard<'static, Vec<PathBuf>> {
+    // a panic while holding the lock can't leave the list inconsistent
+    PENDING.lock().unwrap_or_else(|err| err.into_inner())
+}
+
+#[cfg(any(unix, windows))]
+fn remove_pending() {
+    for temp in pending().iter() {
+        let _ = fs::remove_file(temp);
+    }
+}
+
+#[cfg(unix)]
+impl std::os::fd::AsFd for AtomicFile {
+    fn as_fd(&self) -> std::os::fd::BorrowedFd<'_> {
+        self.file.as_fd()
+    }
+}
@@ -0,0 +1,31 @@
+//! Baseline files listing known violations for check mode.
+//!
+//! A baseline is a plain text file with one path per line, in the form the
+//! path was given on the command line.
+
+use std::collections::HashSet;
+use std::fs;
+use std::io::Result;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+
+use crate::atomic::AtomicFile;
+use crate::format::CheckRecord;
+
+pub fn read(path: &Path) -> Result<HashSet<PathBuf>> {
+    Ok(fs::read_to_string(path)?
+        .lines()
+        .filter(|line| !line.is_empty())
+        .map(PathBuf::from)
+        .collect())
+}
+
+/// Replaces the baseline with the paths of all records that aren't clean.
+pub fn write(path: &Path, records: &[CheckRecord]) -> Result<()> {
+    let mut file = AtomicFile::create(path)?;
+    for record in records.iter().filter(|r| !r.is_clean()) {
+        writeln!(file, "{}", record.path.display())?;
+    }
+    file.commit()
+}
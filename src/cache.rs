@@ -0,0 +1,175 @@
+//! On-disk cache of inputs known to be clean, for repeated check runs.
+//!
+//! Each file is keyed by its path, size and modification time. An entry only
+//! exists while the file was clean when last checked, so a hit means the file
+//! can be skipped without reading it.
+//!
+//! As in git, a file modified no earlier than the cache was written is
+//! "racily clean": it may have been rewritten at the same size within the
+//! granularity of modification times after it was checked, so its entry is
+//! ignored until a later run writes the cache again.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::ErrorKind;
+use std::io::Result;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::Duration;
+use std::time::UNIX_EPOCH;
+
+use crate::atomic::AtomicFile;
+
+/// Start of the first line of a cache file, which is followed by the key of
+/// the check settings. A cache written by another version or with other
+/// settings is discarded.
+const HEADER: &str = concat!("nln-cache ", env!("CARGO_PKG_VERSION"));
+
+/// What a file looked like when it was last checked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Stamp {
+    pub size: u64,
+    mtime: Duration,
+}
+
+impl Stamp {
+    /// Returns the current stamp of a regular file, or `None` if it has none.
+    pub fn of(path: &Path) -> Option<Self> {
+        let metadata = fs::metadata(path).ok().filter(|m| m.is_file())?;
+        let mtime = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+        Some(Self {
+            size: metadata.len(),
+            mtime,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct Cache {
+    key: &'static str,
+    clean: HashMap<PathBuf, Stamp>,
+    /// When the loaded cache file was last modified.
+    written: Option<Duration>,
+}
+
+impl Cache {
+    /// Loads the cache at `path` for the check settings identified by `key`,
+    /// starting empty if it doesn't exist or can't be used.
+    pub fn load(path: &Path, key: &'static str) -> Result<Self> {
+        let mut cache = Self {
+            key,
+            clean: HashMap::new(),
+            written: None,
+        };
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(err) if matches!(err.kind(), ErrorKind::NotFound | ErrorKind::InvalidData) => {
+                return Ok(cache);
+            }
+            Err(err) => return Err(err),
+        };
+        let mut lines = content.lines();
+        if lines.next() == Some(&format!("{HEADER} {key}")) {
+            cache.clean = lines.filter_map(parse_entry).collect();
+            cache.written = fs::metadata(path)
+                .and_then(|metadata| metadata.modified())
+                .ok()
+                .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok());
+        }
+        Ok(cache)
+    }
+
+    pub fn is_clean(&self, path: &Path, stamp: Stamp) -> bool {
+        self.clean.get(path) == Some(&stamp) && self.written.is_some_and(|t| stamp.mtime < t)
+    }
+
+    /// Records the outcome of checking `path` as it looked at `stamp`.
+    pub fn update(&mut self, path: &Path, stamp: Stamp, clean: bool) {
+        if clean {
+            self.clean.insert(path.to_path_buf(), stamp);
+        } else {
+            self.clean.remove(path);
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let mut file = AtomicFile::create(path)?;
+        writeln!(file, "{HEADER} {}", self.key)?;
+        for (path, stamp) in &self.clean {
+            // paths can't be written unambiguously otherwise; skipping them
+            // just means they're always checked
+            let Some(path) = path.to_str().filter(|p| !p.contains(['\n', '\r'])) else {
+                continue;
+            };
+            writeln!(
+                file,
+                "{}\t{}\t{}\t{path}",
+                stamp.size,
+                stamp.mtime.as_secs(),
+                stamp.mtime.subsec_nanos()
+            )?;
+        }
+        file.commit()
+    }
+}
+
+fn parse_entry(line: &str) -> Option<(PathBuf, Stamp)> {
+    let mut fields = line.splitn(4, '\t');
+    let size = fields.next()?.parse().ok()?;
+    let secs = fields.next()?.parse().ok()?;
+    let nanos = fields.next()?.parse().ok()?;
+    let path = fields.next()?;
+    Some((
+        PathBuf::from(path),
+        Stamp {
+            size,
+            mtime: Duration::new(secs, nanos),
+        },
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs::File;
+    use std::time::SystemTime;
+
+    use super::*;
+
+    #[test]
+    fn test_racily_clean() {
+        let dir = env::temp_dir().join(format!("nln-cache-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let input = dir.join("input.txt");
+        let cache_path = dir.join("cache");
+        fs::write(&input, b"a").unwrap();
+        let set_mtime = |path: &Path, mtime: SystemTime| {
+            let file = File::options().write(true).open(path).unwrap();
+            file.set_modified(mtime).unwrap();
+        };
+        let now = SystemTime::now();
+        set_mtime(&input, now);
+        let stamp = Stamp::of(&input).unwrap();
+        let mut cache = Cache::load(&cache_path, "key").unwrap();
+        cache.update(&input, stamp, true);
+        cache.save(&cache_path).unwrap();
+
+        // written in the same instant the file was last modified
+        set_mtime(&cache_path, now);
+        assert!(
+            !Cache::load(&cache_path, "key")
+                .unwrap()
+                .is_clean(&input, stamp)
+        );
+
+        set_mtime(&cache_path, now + Duration::from_secs(1));
+        assert!(
+            Cache::load(&cache_path, "key")
+                .unwrap()
+                .is_clean(&input, stamp)
+        );
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
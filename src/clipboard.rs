@@ -0,0 +1,62 @@
+//! The system clipboard, which `--paste` reads the input from and `--copy`
+//! writes the output to.
+
+#[cfg(not(all(feature = "clipboard", not(target_os = "wasi"))))]
+use std::io;
+
+#[cfg(all(feature = "clipboard", not(target_os = "wasi")))]
+pub use imp::copy;
+#[cfg(all(feature = "clipboard", not(target_os = "wasi")))]
+pub use imp::paste;
+
+#[cfg(all(feature = "clipboard", not(target_os = "wasi")))]
+mod imp {
+    use std::io;
+    use std::str;
+
+    use arboard::Clipboard;
+
+    /// Puts `content` on the clipboard as text.
+    ///
+    /// On X11 the clipboard is served by the process that set it, so it is
+    /// handed to the clipboard manager when nln exits, and is lost without
+    /// one.
+    pub fn copy(content: &[u8]) -> io::Result<()> {
+        let text = str::from_utf8(content).map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                "only UTF-8 text can be copied to the clipboard",
+            )
+        })?;
+        Clipboard::new()
+            .and_then(|mut clipboard| clipboard.set_text(text))
+            .map_err(io::Error::other)
+    }
+
+    /// Takes the text on the clipboard.
+    pub fn paste() -> io::Result<Vec<u8>> {
+        Clipboard::new()
+            .and_then(|mut clipboard| clipboard.get_text())
+            .map(String::into_bytes)
+            .map_err(io::Error::other)
+    }
+}
+
+/// The clipboard needs the `clipboard` feature, and isn't there on WASI.
+#[cfg(not(all(feature = "clipboard", not(target_os = "wasi"))))]
+pub fn copy(_content: &[u8]) -> io::Result<()> {
+    Err(unsupported())
+}
+
+#[cfg(not(all(feature = "clipboard", not(target_os = "wasi"))))]
+pub fn paste() -> io::Result<Vec<u8>> {
+    Err(unsupported())
+}
+
+#[cfg(not(all(feature = "clipboard", not(target_os = "wasi"))))]
+fn unsupported() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::Unsupported,
+        "nln was built without clipboard support",
+    )
+}
@@ -0,0 +1,81 @@
+//! Copying the content of a regular file up to its trailing newlines without
+//! reading it, which `io::copy` does with copy_file_range(2) or sendfile(2)
+//! on Linux.
+
+use std::fs::File;
+use std::io;
+use std::io::BufRead;
+use std::io::BufReader;
+use std::io::Read;
+use std::io::Seek;
+use std::io::Write;
+
+use nln::Error;
+use nln::Stats;
+
+/// Stdin as a file, if it is a regular one.
+#[cfg(unix)]
+pub fn stdin_file() -> Option<File> {
+    use std::os::fd::AsFd;
+
+    let file = File::from(io::stdin().as_fd().try_clone_to_owned().ok()?);
+    file.metadata().ok()?.is_file().then_some(file)
+}
+
+/// Whether `output` is a regular file or a socket, which the kernel copies
+/// into without a pipe in between. Splicing into a pipe turned out slower
+/// than reading and writing.
+#[cfg(unix)]
+pub fn accepts(output: &impl std::os::fd::AsFd) -> bool {
+    use std::os::unix::fs::FileTypeExt;
+
+    output
+        .as_fd()
+        .try_clone_to_owned()
+        .map(File::from)
+        .and_then(|file| file.metadata())
+        .is_ok_and(|metadata| metadata.is_file() || metadata.file_type().is_socket())
+}
+
+/// Stdin can only be read as a stream elsewhere.
+#[cfg(not(unix))]
+pub fn stdin_file() -> Option<File> {
+    None
+}
+
+#[cfg(not(unix))]
+pub fn accepts<O>(_output: &O) -> bool {
+    false
+}
+
+/// Strips trailing newlines from the rest of `input` to `output` with the
+/// default options, locating them with a scan back from the end of the file
+/// first.
+pub fn strip(mut input: &File, output: &mut impl Write) -> Result<Stats, Error> {
+    let tail = nln::check_seekable(input).map_err(Error::Read)?;
+    let start = input.stream_position().map_err(Error::Read)?;
+    let content = tail.trailing_start - start;
+    // reads and writes happen in the kernel, so neither can be told apart
+    let copied = io::copy(&mut input.take(content), output).map_err(Error::Write)?;
+    output.flush().map_err(Error::Write)?;
+
+    let mut stats = Stats::default();
+    stats.bytes_in = copied;
+    stats.bytes_out = copied;
+    stats.chunks = 1;
+    // only the newlines are read, to count them
+    let mut trailing = BufReader::new(input.take(tail.trailing_bytes()));
+    loop {
+        let buffer = trailing.fill_buf().map_err(Error::Read)?;
+        if buffer.is_empty() {
+            break;
+        }
+        let lf = buffer.iter().filter(|&&b| b == b'\n').count();
+        stats.lf_stripped += lf as u64;
+        stats.cr_stripped += (buffer.len() - lf) as u64;
+        stats.bytes_in += buffer.len() as u64;
+        let len = buffer.len();
+        trailing.consume(len);
+    }
+    Ok(stats)
+}
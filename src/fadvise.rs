@@ -0,0 +1,32 @@
+//! Telling the kernel how input files are read, so batch runs get more
+//! readahead and don't fill the page cache with files read only once.
+
+use std::fs::File;
+
+/// Announces that `file` is about to be read from start to end.
+#[cfg(target_os = "linux")]
+pub fn sequential(file: &File) {
+    advise(file, libc::POSIX_FADV_SEQUENTIAL);
+}
+
+/// Announces that `file` has been read and its cached pages can go.
+#[cfg(target_os = "linux")]
+pub fn done(file: &File) {
+    advise(file, libc::POSIX_FADV_DONTNEED);
+}
+
+#[cfg(target_os = "linux")]
+fn advise(file: &File, advice: libc::c_int) {
+    use std::os::fd::AsRawFd;
+
+    // SAFETY: posix_fadvise only takes a file descriptor, and hints that
+    // fail, as on pipes, are of no consequence
+    unsafe { libc::posix_fadvise(file.as_raw_fd(), 0, 0, advice) };
+}
+
+/// Only Linux builds link libc, so elsewhere no hints are given.
+#[cfg(not(target_os = "linux"))]
+pub fn sequential(_file: &File) {}
+
+#[cfg(not(target_os = "linux"))]
+pub fn done(_file: &File) {}
@@ -0,0 +1,489 @@
+//! Output formats for check mode results.
+
+use std::io::Result;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+
+pub use crate::args::Format;
+
+/// A one-based line and column in an input.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Position {
+    pub line: usize,
+    /// The column counted in bytes.
+    pub column: usize,
+    /// The column counted in code points, as SARIF takes it.
+    pub char_column: usize,
+}
+
+impl Position {
+    pub const START: Self = Self {
+        line: 1,
+        column: 1,
+        char_column: 1,
+    };
+
+    /// Returns the position reached by moving past `bytes`.
+    pub fn advance(self, bytes: &[u8]) -> Self {
+        // every byte but a UTF-8 continuation byte starts a code point, or
+        // stands for one if it isn't valid UTF-8
+        let chars = |bytes: &[u8]| bytes.iter().filter(|&&b| b & 0xC0 != 0x80).count();
+        match bytes.iter().rposition(|&b| b == b'\n') {
+            Some(last) => Self {
+                line: self.line + bytes.iter().filter(|&&b| b == b'\n').count(),
+                column: bytes.len() - last,
+                char_column: chars(&bytes[last + 1..]) + 1,
+            },
+            None => Self {
+                line: self.line,
+                column: self.column + bytes.len(),
+                char_column: self.char_column + chars(bytes),
+            },
+        }
+    }
+}
+
+/// Outcome of checking a single input for trailing newlines.
+pub struct CheckRecord {
+    pub path: PathBuf,
+    /// Byte offset at which the trailing newlines start.
+    pub offset: usize,
+    /// Where the trailing newlines start.
+    pub start: Position,
+    /// The end of the input.
+    pub end: Position,
+    pub bytes_stripped: usize,
+    pub eol_kind: Option<&'static str>,
+    /// The input doesn't end with a newline although one is required.
+    pub missing_final_newline: bool,
+    /// The input is listed in the baseline, so its trailing newlines are
+    /// ignored.
+    pub baselined: bool,
+}
+
+impl CheckRecord {
+    /// A record for an input of `len` bytes known to be clean without reading
+    /// it.
+    pub fn clean(path: &Path, len: u64) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            offset: len as usize,
+            // only reported for violations
+            start: Position::default(),
+            end: Position::default(),
+            bytes_stripped: 0,
+            eol_kind: None,
+            missing_final_newline: false,
+            baselined: false,
+        }
+    }
+
+    pub fn status(&self) -> &'static str {
+        if self.is_clean() {
+            "ok"
+        } else if self.baselined {
+            "baselined"
+        } else if self.missing_final_newline {
+            "missing"
+        } else {
+            "trailing"
+        }
+    }
+
+    /// Describes the problem, e.g. "2 trailing newline bytes (crlf)".
+    pub fn message(&self) -> String {
+        if self.missing_final_newline {
+            return "missing final newline".to_string();
+        }
+        format!(
+            "{} trailing newline bytes ({})",
+            self.bytes_stripped,
+            self.eol_kind.unwrap_or_default()
+        )
+    }
+
+    /// Whether the input has no problem, regardless of the baseline.
+    pub fn is_clean(&self) -> bool {
+        self.bytes_stripped == 0 && !self.missing_final_newline
+    }
+
+    /// Whether the input has a problem that should be reported.
+    pub fn is_violation(&self) -> bool {
+        !self.is_clean() && !self.baselined
+    }
+
+    /// Id of the rule the input violates.
+    pub fn rule_id(&self) -> &'static str {
+        if self.missing_final_newline {
+            "missing-final-newline"
+        } else {
+            "trailing-newline"
+        }
+    }
+}
+
+/// Writes the check results to `out` in the given format.
+pub fn write(out: &mut impl Write, format: Format, records: &[CheckRecord]) -> Result<()> {
+    match format {
+        Format::Text => write_text(out, records),
+        Format::Json => write_json(out, records),
+        Format::Sarif => write_sarif(out, records),
+        Format::Rdjson => write_rdjson(out, records),
+        Format::Junit => write_junit(out, records),
+        Format::Tap => write_tap(out, records),
+        Format::List { null } => write_list(out, records, if null { b'\0' } else { b'\n' }),
+    }
+}
+
+fn write_list(out: &mut impl Write, records: &[CheckRecord], terminator: u8) -> Result<()> {
+    for record in records.iter().filter(|r| r.is_violation()) {
+        // write the path as-is so it round-trips through xargs even if it
+        // isn't valid UTF-8
+        out.write_all(record.path.as_os_str().as_encoded_bytes())?;
+        out.write_all(&[terminator])?;
+    }
+    Ok(())
+}
+
+fn write_text(out: &mut impl Write, records: &[CheckRecord]) -> Result<()> {
+    for record in records.iter().filter(|r| r.is_violation()) {
+        writeln!(out, "{}: {}", record.path.display(), record.message())?;
+    }
+    Ok(())
+}
+
+fn write_json(out: &mut impl Write, records: &[CheckRecord]) -> Result<()> {
+    write!(out, "[")?;
+    for (i, record) in records.iter().enumerate() {
+        if i > 0 {
+            write!(out, ",")?;
+        }
+        write!(out, "{{\"path\":")?;
+        write_json_string(out, &record.path.to_string_lossy())?;
+        write!(
+            out,
+            ",\"status\":\"{}\",\"bytes_stripped\":{},\"eol_kind\":",
+            record.status(),
+            record.bytes_stripped
+        )?;
+        match record.eol_kind {
+            Some(kind) => write!(out, "\"{kind}\"}}")?,
+            None => write!(out, "null}}")?,
+        }
+    }
+    writeln!(out, "]")
+}
+
+/// Writes a SARIF 2.1.0 log with one result per input that has trailing
+/// newlines, located at the trailing run at the end of the file.
+fn write_sarif(out: &mut impl Write, records: &[CheckRecord]) -> Result<()> {
+    write!(
+        out,
+        "{{\"version\":\"2.1.0\",\
+\"$schema\":\"https://json.schemastore.org/sarif-2.1.0.json\",\
+\"runs\":[{{\"tool\":{{\"driver\":{{\"name\":\"{}\",\"version\":\"{}\",\
+\"informationUri\":\"{}\",\"rules\":[\
+{{\"id\":\"trailing-newline\",\"shortDescription\":{{\"text\":\"File ends with trailing newlines\"}}}},\
+{{\"id\":\"missing-final-newline\",\"shortDescription\":{{\"text\":\"File doesn't end with a newline\"}}}}\
+]}}}},\
+\"columnKind\":\"unicodeCodePoints\",\"results\":[",
+        env!("CARGO_PKG_NAME"),
+        env!("CARGO_PKG_VERSION"),
+        env!("CARGO_PKG_REPOSITORY"),
+    )?;
+    for (i, record) in records.iter().filter(|r| r.is_violation()).enumerate() {
+        if i > 0 {
+            write!(out, ",")?;
+        }
+        write!(
+            out,
+            "{{\"ruleId\":\"{}\",\"level\":\"error\",\
+\"message\":{{\"text\":\"{}\"}},\
+\"locations\":[{{\"physicalLocation\":{{\"artifactLocation\":{{\"uri\":",
+            record.rule_id(),
+            record.message()
+        )?;
+        write_uri(out, &record.path)?;
+        write!(
+            out,
+            "}},\"region\":{{\"startLine\":{},\"startColumn\":{},\"endLine\":{},\"endColumn\":{},\
+\"byteOffset\":{},\"byteLength\":{}}}}}}}]}}",
+            record.start.line,
+            record.start.char_column,
+            record.end.line,
+            record.end.char_column,
+            record.offset,
+            record.bytes_stripped
+        )?;
+    }
+    writeln!(out, "]}}]}}")
+}
+
+/// Writes reviewdog's rdjson format, with a suggestion fixing each violation.
+fn write_rdjson(out: &mut impl Write, records: &[CheckRecord]) -> Result<()> {
+    write!(
+        out,
+        "{{\"source\":{{\"name\":\"{}\",\"url\":\"{}\"}},\"severity\":\"ERROR\",\"diagnostics\":[",
+        env!("CARGO_PKG_NAME"),
+        env!("CARGO_PKG_REPOSITORY"),
+    )?;
+    for (i, record) in records.iter().filter(|r| r.is_violation()).enumerate() {
+        if i > 0 {
+            write!(out, ",")?;
+        }
+        let range = format!(
+            "{{\"start\":{{\"line\":{},\"column\":{}}},\"end\":{{\"line\":{},\"column\":{}}}}}",
+            record.start.line, record.start.column, record.end.line, record.end.column
+        );
+        write!(
+            out,
+            "{{\"message\":\"{}\",\"location\":{{\"path\":",
+            record.message()
+        )?;
+        write_json_string(out, &record.path.to_string_lossy())?;
+        write!(
+            out,
+            ",\"range\":{range}}},\"severity\":\"ERROR\",\"code\":{{\"value\":\"{}\"}},\
+\"suggestions\":[{{\"range\":{range},\"text\":\"{}\"}}]}}",
+            record.rule_id(),
+            if record.missing_final_newline {
+                "\\n"
+            } else {
+                ""
+            }
+        )?;
+    }
+    writeln!(out, "]}}")
+}
+
+/// Writes a JUnit XML report where each input is a test case, failing if it
+/// has trailing newlines and skipped if those are baselined.
+fn write_junit(out: &mut impl Write, records: &[CheckRecord]) -> Result<()> {
+    let failures = records.iter().filter(|r| r.is_violation()).count();
+    let skipped = records.iter().filter(|r| r.status() == "baselined").count();
+    let counts = format!(
+        "tests=\"{}\" failures=\"{failures}\" skipped=\"{skipped}\"",
+        records.len()
+    );
+    writeln!(out, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>")?;
+    writeln!(
+        out,
+        "<testsuites name=\"{0}\" {counts}>",
+        env!("CARGO_PKG_NAME")
+    )?;
+    writeln!(
+        out,
+        "  <testsuite name=\"{0}\" {counts}>",
+        env!("CARGO_PKG_NAME")
+    )?;
+    for record in records {
+        write!(
+            out,
+            "    <testcase classname=\"{}\" name=\"",
+            env!("CARGO_PKG_NAME")
+        )?;
+        write_xml_escaped(out, &record.path.to_string_lossy())?;
+        match record.status() {
+            "trailing" | "missing" => writeln!(
+                out,
+                "\">\n      <failure type=\"{}\" message=\"{}\"/>\n    </testcase>",
+                record.rule_id(),
+                record.message()
+            )?,
+            "baselined" => writeln!(
+                out,
+                "\">\n      <skipped message=\"baselined: {}\"/>\n    </testcase>",
+                record.message()
+            )?,
+            _ => writeln!(out, "\"/>")?,
+        }
+    }
+    writeln!(out, "  </testsuite>")?;
+    writeln!(out, "</testsuites>")
+}
+
+/// Writes a TAP version 14 stream where each input is a test point, failing
+/// if it has trailing newlines and skipped if those are baselined.
+fn write_tap(out: &mut impl Write, records: &[CheckRecord]) -> Result<()> {
+    writeln!(out, "TAP version 14")?;
+    writeln!(out, "1..{}", records.len())?;
+    for (i, record) in records.iter().enumerate() {
+        let number = i + 1;
+        // `#` starts a directive, so it must be escaped in descriptions, and a
+        // line break would start another line of the stream
+        let path = record
+            .path
+            .to_string_lossy()
+            .replace('\\', "\\\\")
+            .replace('#', "\\#")
+            .replace('\n', "\\n")
+            .replace('\r', "\\r");
+        match record.status() {
+            "trailing" | "missing" => {
+                writeln!(out, "not ok {number} - {path}")?;
+                writeln!(out, "  ---")?;
+                writeln!(out, "  message: '{}'", record.message())?;
+                writeln!(out, "  rule: {}", record.rule_id())?;
+                writeln!(out, "  ...")?;
+            }
+            "baselined" => writeln!(out, "ok {number} - {path} # SKIP baselined")?,
+            _ => writeln!(out, "ok {number} - {path}")?,
+        }
+    }
+    Ok(())
+}
+
+fn write_xml_escaped(out: &mut impl Write, s: &str) -> Result<()> {
+    for c in s.chars() {
+        match c {
+            '&' => write!(out, "&amp;")?,
+            '<' => write!(out, "&lt;")?,
+            '>' => write!(out, "&gt;")?,
+            '"' => write!(out, "&quot;")?,
+            '\'' => write!(out, "&apos;")?,
+            // XML 1.0 can't represent most control characters, even escaped
+            c if c.is_control() && !matches!(c, '\t' | '\n' | '\r') => write!(out, "\u{fffd}")?,
+            c if c.is_control() => write!(out, "&#{};", c as u32)?,
+            c => write!(out, "{c}")?,
+        }
+    }
+    Ok(())
+}
+
+fn write_json_string(out: &mut impl Write, s: &str) -> Result<()> {
+    write!(out, "\"")?;
+    for c in s.chars() {
+        match c {
+            '"' => write!(out, "\\\"")?,
+            '\\' => write!(out, "\\\\")?,
+            '\n' => write!(out, "\\n")?,
+            '\r' => write!(out, "\\r")?,
+            '\t' => write!(out, "\\t")?,
+            c if c.is_control() => write!(out, "\\u{:04x}", c as u32)?,
+            c => write!(out, "{c}")?,
+        }
+    }
+    write!(out, "\"")
+}
+
+/// Writes `path` as a JSON string holding a URI reference, as SARIF expects
+/// of artifact locations: percent-encoded, with `/` separators, and as a
+/// `file` URI if the path is absolute.
+fn write_uri(out: &mut impl Write, path: &Path) -> Result<()> {
+    let mut path = path.to_string_lossy().into_owned();
+    if cfg!(windows) {
+        path = path.replace('\\', "/");
+    }
+    let absolute = Path::new(&path).is_absolute() || path.starts_with('/');
+    write!(out, "\"")?;
+    if absolute {
+        write!(out, "file://")?;
+        if !path.starts_with('/') {
+            write!(out, "/")?;
+        }
+    }
+    for b in path.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' => {
+                write!(out, "{}", b as char)?;
+            }
+            // a colon in a relative reference would be read as a scheme, but
+            // in a file URI it follows a drive letter
+            b':' if absolute => write!(out, ":")?,
+            b => write!(out, "%{b:02X}")?,
+        }
+    }
+    write!(out, "\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::CheckRecord;
+    use super::Position;
+    use super::write_json_string;
+    use super::write_tap;
+    use super::write_uri;
+    use super::write_xml_escaped;
+
+    #[test]
+    fn test_position_advance() {
+        assert_eq!(Position::START.advance(b""), Position::START);
+        assert_eq!(
+            Position::START.advance(b"abc"),
+            Position {
+                line: 1,
+                column: 4,
+                char_column: 4
+            }
+        );
+        assert_eq!(
+            Position::START.advance(b"ab\ncd\r\n"),
+            Position {
+                line: 3,
+                column: 1,
+                char_column: 1
+            }
+        );
+        assert_eq!(
+            Position::START.advance("ab\n\u{e9}\u{1f600}".as_bytes()),
+            Position {
+                line: 2,
+                column: 7,
+                char_column: 3
+            }
+        );
+        assert_eq!(
+            Position::START.advance("\u{e9}".as_bytes()).advance(b"c"),
+            Position {
+                line: 1,
+                column: 4,
+                char_column: 3
+            }
+        );
+    }
+
+    #[test]
+    fn test_uri() {
+        let uri = |path: &str| {
+            let mut buf = Vec::new();
+            write_uri(&mut buf, Path::new(path)).unwrap();
+            String::from_utf8(buf).unwrap()
+        };
+        assert_eq!(uri("src/main.rs"), r#""src/main.rs""#);
+        assert_eq!(uri("a b/50%#1.txt"), r#""a%20b/50%25%231.txt""#);
+        assert_eq!(uri("c:d\u{e9}"), r#""c%3Ad%C3%A9""#);
+        #[cfg(unix)]
+        assert_eq!(uri("/tmp/a:b"), r#""file:///tmp/a:b""#);
+    }
+
+    #[test]
+    fn test_json_string() {
+        let mut buf = Vec::new();
+        write_json_string(&mut buf, "a\"b\\c\nd\u{1}").unwrap();
+        assert_eq!(str::from_utf8(&buf).unwrap(), r#""a\"b\\c\nd\u0001""#);
+    }
+
+    #[test]
+    fn test_tap_escapes_paths() {
+        let mut buf = Vec::new();
+        let record = CheckRecord::clean(Path::new("a\nok 2 - b\r#c\\"), 0);
+        write_tap(&mut buf, &[record]).unwrap();
+        assert_eq!(
+            str::from_utf8(&buf).unwrap(),
+            "TAP version 14\n1..1\nok 1 - a\\nok 2 - b\\r\\#c\\\\\n"
+        );
+    }
+
+    #[test]
+    fn test_xml_escaped() {
+        let mut buf = Vec::new();
+        write_xml_escaped(&mut buf, "<a & 'b'>\"\t\u{1}").unwrap();
+        assert_eq!(
+            str::from_utf8(&buf).unwrap(),
+            "&lt;a &amp; &apos;b&apos;&gt;&quot;&#9;\u{fffd}"
+        );
+    }
+}
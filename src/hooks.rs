@@ -0,0 +1,120 @@
+//! The `nln hooks` subcommand for managing git hooks.
+
+use std::fs;
+use std::io::Error;
+use std::io::ErrorKind;
+use std::io::Result;
+use std::path::Path;
+use std::path::PathBuf;
+use std::process::Command;
+
+/// Marks hooks written by nln so reinstalling doesn't chain a hook to itself.
+const MARKER: &str = "# installed by nln hooks install";
+
+/// Suffix given to a pre-existing hook that the nln hook runs first.
+const CHAINED_SUFFIX: &str = "pre-nln";
+
+/// Runs `nln hooks <ARGS>`, returning the exit code.
+pub fn run(mut args: impl Iterator<Item = String>) -> i32 {
+    match (args.next().as_deref(), args.next()) {
+        (Some("install"), None) => match install() {
+            Ok(hook) => {
+                println!("Installed pre-commit hook at {}", hook.display());
+                0
+            }
+            Err(err) => {
+                eprintln!("Failed to install pre-commit hook: {err}");
+                2
+            }
+        },
+        _ => {
+            eprintln!(
+                "Usage: {} hooks install
+Use --help for usage information",
+                env!("CARGO_PKG_NAME")
+            );
+            1
+        }
+    }
+}
+
+/// Writes a pre-commit hook that checks staged files, returning its path.
+///
+/// The hook checks the content in the index rather than in the working tree,
+/// which differs after `git add -p` or an edit made after staging.
+///
+/// The hooks directory comes from git, so `core.hooksPath` is respected. An
+/// existing hook that nln didn't write is kept and run before the check.
+fn install() -> Result<PathBuf> {
+    let dir = hooks_dir()?;
+    fs::create_dir_all(&dir)?;
+    let hook = dir.join("pre-commit");
+
+    match fs::read_to_string(&hook) {
+        Ok(existing) if existing.contains(MARKER) => {}
+        Ok(_) => {
+            let chained = dir.join(format!("pre-commit.{CHAINED_SUFFIX}"));
+            if chained.exists() {
+                return Err(Error::other(format!(
+                    "both {} and {} exist, refusing to overwrite either",
+                    hook.display(),
+                    chained.display()
+                )));
+            }
+            fs::rename(&hook, &chained)?;
+        }
+        Err(err) if err.kind() == ErrorKind::NotFound => {}
+        Err(err) => return Err(err),
+    }
+
+    fs::write(&hook, script())?;
+    make_executable(&hook)?;
+    Ok(hook)
+}
+
+fn hooks_dir() -> Result<PathBuf> {
+    let output = Command::new("git")
+        .args(["rev-parse", "--git-path", "hooks"])
+        .output()?;
+    if !output.status.success() {
+        return Err(Error::other(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+    let dir = String::from_utf8(output.stdout).map_err(Error::other)?;
+    Ok(PathBuf::from(dir.trim_end_matches(['\r', '\n'])))
+}
+
+fn script() -> String {
+    format!(
+        r#"#!/bin/sh
+{MARKER}
+chained="$(dirname "$0")/pre-commit.{CHAINED_SUFFIX}"
+if [ -x "$chained" ]; then
+    "$chained" "$@" || exit $?
+fi
+git diff --cached --name-only --diff-filter=ACMR -z |
+    xargs -0 sh -c '
+status=0
+for f; do
+    if ! git cat-file blob ":0:$f" | nln --check - >/dev/null; then
+        printf "%s: staged with trailing newlines\n" "$f" >&2
+        status=1
+    fi
+done
+exit $status' nln
+"#
+    )
+}
+
+#[cfg(unix)]
+fn make_executable(path: &Path) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    fs::set_permissions(path, fs::Permissions::from_mode(0o755))
+}
+
+#[cfg(not(unix))]
+fn make_executable(_path: &Path) -> Result<()> {
+    Ok(())
+}
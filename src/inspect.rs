@@ -0,0 +1,67 @@
+//! The `nln inspect` subcommand reporting the line endings of files.
+
+use std::fs::File;
+use std::io::BufReader;
+use std::io::Result;
+use std::io::Write;
+use std::io::stdin;
+use std::io::stdout;
+use std::path::Path;
+use std::path::PathBuf;
+
+use nln::CheckResult;
+use nln::check;
+
+/// Runs `nln inspect [FILE]...`, returning the exit code.
+pub fn run(args: impl Iterator<Item = String>) -> i32 {
+    let mut files: Vec<PathBuf> = args.map(PathBuf::from).collect();
+    if files.is_empty() {
+        files.push(PathBuf::from("-"));
+    }
+
+    let mut out = stdout().lock();
+    let mut failed = false;
+    for path in &files {
+        let composition = if path == Path::new("-") {
+            check(stdin().lock())
+        } else {
+            File::open(path).and_then(|file| check(BufReader::new(file)))
+        };
+        let written = match composition {
+            Ok(composition) => write(&mut out, path, &composition),
+            Err(err) => {
+                eprintln!("{}: {err}", path.display());
+                failed = true;
+                continue;
+            }
+        };
+        if let Err(err) = written {
+            eprintln!("{err}");
+            return 2;
+        }
+    }
+    if failed { 2 } else { 0 }
+}
+
+fn write(out: &mut impl Write, path: &Path, composition: &CheckResult) -> Result<()> {
+    writeln!(
+        out,
+        "{}:
+  lf:                   {}
+  crlf:                 {}
+  cr:                   {}
+  trailing terminators: {} ({} bytes)
+  final newline:        {}",
+        path.display(),
+        composition.lf,
+        composition.crlf,
+        composition.cr,
+        composition.trailing,
+        composition.trailing_bytes,
+        if composition.has_trailing_newlines() {
+            "yes"
+        } else {
+            "no"
+        }
+    )
+}
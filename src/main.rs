@@ -1,32 +1,819 @@
+use std::collections::HashSet;
 use std::env;
+use std::fs::File;
+use std::fs::OpenOptions;
+use std::io::BufRead;
+use std::io::BufReader;
+use std::io::BufWriter;
+use std::io::Cursor;
+use std::io::ErrorKind;
+use std::io::IntoInnerError;
+use std::io::IsTerminal;
+use std::io::Read;
 use std::io::Result;
+use std::io::Seek;
+use std::io::SeekFrom;
+use std::io::StdoutLock;
+use std::io::Write;
+use std::io::stderr;
 use std::io::stdin;
 use std::io::stdout;
+use std::path::Path;
+use std::path::PathBuf;
 use std::process;
+use std::time::Duration;
+use std::time::Instant;
 
-use nln::snickerdoodle;
+use nln::Error;
+use nln::LineEnding;
+use nln::Options;
+use nln::Stats;
+
+use crate::args::Config;
+use crate::args::GitFilter;
+use crate::atomic::AtomicFile;
+use crate::cache::Cache;
+use crate::cache::Stamp;
+use crate::format::CheckRecord;
+use crate::format::Format;
+use crate::format::Position;
+use crate::pool::Pool;
+use crate::summary::Summary;
+use crate::timeout::TimeoutReader;
+use crate::uring::IoBackend;
+use crate::uring::Uring;
+
+mod args;
+mod atomic;
+mod baseline;
+mod cache;
+mod clipboard;
+mod copy;
+mod fadvise;
+mod format;
+mod hooks;
+mod inspect;
+mod mmap;
+mod osc52;
+mod pool;
+mod splice;
+mod summary;
+mod timeout;
+mod tmux;
+mod uring;
 
 fn main() -> Result<()> {
-    // If there are arguments (beyond program name), process them
-    if let Some(arg) = env::args().nth(1) {
-        match arg.as_str() {
-            "--help" | "-h" => {
-                print_help();
-                return Ok(());
-            }
-            "--version" | "-v" => {
-                print_version();
-                return Ok(());
+    match env::args().nth(1).as_deref() {
+        Some("hooks") => process::exit(hooks::run(env::args().skip(2))),
+        Some("inspect") => process::exit(inspect::run(env::args().skip(2))),
+        _ => {}
+    }
+
+    let config =
+        args::parse(env::args().skip(1)).unwrap_or_else(|message| exit_usage_error(&message));
+    if config.help {
+        print_help();
+        return Ok(());
+    }
+    if config.version {
+        print_version();
+        return Ok(());
+    }
+    let Config {
+        report,
+        check,
+        format,
+        git_filter,
+        timeout,
+        timeout_flush,
+        broken_pipe_error,
+        output,
+        copy,
+        paste,
+        osc52,
+        tmux_buffer,
+        in_place,
+        fail_fast,
+        baseline,
+        write_baseline,
+        cache,
+        ensure_final_newline,
+        summary,
+        options,
+        configured,
+        limited,
+        eol_auto,
+        mmap,
+        fadvise,
+        pipeline,
+        buffer_size,
+        read_buffer,
+        write_buffer,
+        io_backend,
+        files,
+        ..
+    } = config;
+
+    if check {
+        let options = CheckOptions {
+            format,
+            fail_fast,
+            baseline,
+            write_baseline,
+            cache,
+            ensure_final_newline,
+            summary,
+            mmap,
+            fadvise,
+            broken_pipe_error,
+        };
+        process::exit(run_check(&files, &options));
+    }
+
+    let smudge = git_filter == Some(GitFilter::Smudge);
+    // the default options only drop bytes at the end, so content can be moved
+    // by the kernel without nln reading it
+    let plain = io_backend == IoBackend::Auto && !configured && !limited;
+    let mut ring = match io_backend.ring() {
+        Ok(ring) => ring,
+        Err(err) => {
+            eprintln!("--io-backend uring is not available: {err}");
+            process::exit(1);
+        }
+    };
+
+    if in_place {
+        let settings = InPlaceOptions {
+            eol_auto,
+            smudge,
+            mmap,
+            fadvise,
+            plain,
+            summary,
+        };
+        process::exit(run_in_place(&files, &options, &settings, ring.as_mut()));
+    }
+
+    // the output goes somewhere other than stdout, and all at once
+    let to_clipboard = copy || osc52 || tmux_buffer.is_some();
+
+    let mut options = options;
+    let read_size = stream_buffer(read_buffer.or(buffer_size), stdin().is_terminal())
+        .unwrap_or(options.buffer_size());
+    // the ring reads stdin itself, so it can only be used when nothing else does
+    if timeout.is_some() || eol_auto || pipeline || to_clipboard || paste {
+        ring = None;
+    }
+    let mut pasted = paste.then(|| {
+        clipboard::paste().unwrap_or_else(|err| {
+            eprintln!("{}", Error::Read(err));
+            process::exit(1);
+        })
+    });
+    // with a regular file on stdin, the trailing newlines can be found first
+    let stdin_file = if plain
+        && !paste
+        && !pipeline
+        && timeout.is_none()
+        && (output.is_some() || copy::accepts(&stdout()))
+    {
+        copy::stdin_file()
+    } else {
+        None
+    };
+    let passthrough = plain
+        && !paste
+        && !pipeline
+        && timeout.is_none()
+        && output.is_none()
+        && splice::available(&stdin(), &stdout());
+    let input: Box<dyn BufRead> = match timeout {
+        // the whole input decides the line ending, so it has to be read first,
+        // and the clipboard is read whole anyway
+        None if eol_auto || pasted.is_some() => {
+            let content = pasted.take().unwrap_or_else(|| {
+                let mut content = Vec::new();
+                if let Err(err) = stdin().lock().read_to_end(&mut content) {
+                    eprintln!("{}", Error::Read(err));
+                    process::exit(1);
+                }
+                content
+            });
+            if eol_auto {
+                let line_ending = nln::check(content.as_slice())
+                    .ok()
+                    .and_then(|result| result.dominant_line_ending());
+                options = with_line_ending(&options, line_ending);
             }
-            _ => {
-                eprint_unknown_argument(&arg);
+            Box::new(Cursor::new(content))
+        }
+        Some(timeout) => match TimeoutReader::spawn(stdin(), timeout, timeout_flush) {
+            Ok(reader) => Box::new(reader),
+            Err(err) => {
+                eprintln!("--timeout is not available: {err}");
                 process::exit(1);
             }
+        },
+        None => Box::new(BufReader::with_capacity(read_size, stdin().lock())),
+    };
+    // a large file on stdin going to a file is read on a separate thread, so
+    // reading overlaps with writing
+    let pipeline = pipeline
+        || (output.is_some()
+            && stdin_file.is_none()
+            && ring.is_none()
+            && !eol_auto
+            && !paste
+            && timeout.is_none()
+            && copy::stdin_file().is_some_and(|file| overlaps(&file)));
+    let pipeline = pipeline.then_some(read_size);
+    // without a buffer, stdout is flushed at every newline
+    let write_buffer = stream_buffer(
+        write_buffer.or(buffer_size),
+        output.is_none() && stdout().is_terminal(),
+    );
+    let smudge = smudge.then_some(&options);
+    let result = match output {
+        // clipboards take the whole output at once
+        None if to_clipboard => {
+            let mut content = Vec::new();
+            strip(&mut content, report, smudge, |output| {
+                process_stdin(input, output, &options, pipeline)
+            })
+            .and_then(|_| {
+                if copy {
+                    clipboard::copy(&content).map_err(Error::Write)?;
+                }
+                if osc52 {
+                    osc52::copy(&content).map_err(Error::Write)?;
+                }
+                if let Some(name) = &tmux_buffer {
+                    tmux::load_buffer(&content, name.as_deref()).map_err(Error::Write)?;
+                }
+                Ok(())
+            })
+        }
+        Some(path) => AtomicFile::create(&path)
+            .map_err(Error::Write)
+            .and_then(|mut file| {
+                match (&stdin_file, &mut ring) {
+                    (Some(input), _) => strip(&mut file, report, smudge, |file| {
+                        copy::strip(input, &mut file.file())
+                    })?,
+                    (None, Some(ring)) => strip(&mut file, report, smudge, |file| {
+                        ring.process(&stdin(), file, &options)
+                    })?,
+                    (None, None) => strip(
+                        BufWriter::with_capacity(
+                            write_buffer.unwrap_or(options.buffer_size()),
+                            &mut file,
+                        ),
+                        report,
+                        smudge,
+                        |output| process_stdin(input, output, &options, pipeline),
+                    )?,
+                };
+                file.commit().map_err(Error::Write)
+            }),
+        None => match (&stdin_file, &mut ring) {
+            (Some(input), _) => strip(stdout().lock(), report, smudge, |output| {
+                copy::strip(input, output)
+            }),
+            _ if passthrough => strip(stdout().lock(), report, smudge, |output| {
+                splice::strip(&stdin(), output)
+            }),
+            (None, Some(ring)) => strip(stdout().lock(), report, smudge, |output| {
+                ring.process(&stdin(), output, &options)
+            }),
+            (None, None) => match write_buffer {
+                Some(size) => strip(
+                    BufWriter::with_capacity(size, stdout().lock()),
+                    report,
+                    smudge,
+                    |output| process_stdin(input, output, &options, pipeline),
+                ),
+                None => strip(stdout().lock(), report, smudge, |output| {
+                    process_stdin(input, output, &options, pipeline)
+                }),
+            },
+        }
+        .map(drop),
+    };
+    match result {
+        Ok(()) => Ok(()),
+        Err(Error::Write(err)) if err.kind() == ErrorKind::BrokenPipe && !broken_pipe_error => {
+            process::exit(BROKEN_PIPE_STATUS)
+        }
+        Err(err) => {
+            eprintln!("{err}");
+            process::exit(1);
+        }
+    }
+}
+
+/// Buffer size for stdin and stdout when they aren't terminals, large enough
+/// that system calls don't dominate on big pipes.
+const STREAM_BUFFER_SIZE: usize = 256 * 1024;
+
+/// Sizes the buffer of stdin or stdout: as given, or large unless it is a
+/// terminal, where output should show up as soon as it is produced.
+fn stream_buffer(explicit: Option<usize>, terminal: bool) -> Option<usize> {
+    explicit.or((!terminal).then_some(STREAM_BUFFER_SIZE))
+}
+
+/// Exit status used when stdout is closed early, matching a process killed by
+/// SIGPIPE (128 + 13) as the shell reports it.
+const BROKEN_PIPE_STATUS: i32 = 141;
+
+/// An output that may hold back some of what is written to it.
+trait Finish: Write {
+    /// Writes out what is held back, without flushing what it writes to,
+    /// which `--no-final-flush` leaves alone.
+    fn finish(self) -> Result<()>;
+}
+
+impl<W: Write> Finish for BufWriter<W> {
+    fn finish(self) -> Result<()> {
+        self.into_inner()
+            .map(drop)
+            .map_err(IntoInnerError::into_error)
+    }
+}
+
+impl Finish for &mut AtomicFile {
+    fn finish(self) -> Result<()> {
+        Ok(())
+    }
+}
+
+impl Finish for &mut Vec<u8> {
+    fn finish(self) -> Result<()> {
+        Ok(())
+    }
+}
+
+impl Finish for StdoutLock<'_> {
+    fn finish(self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Strips trailing newlines to `output` with `process`, which is handed the
+/// output, and finishes it as `--git-filter smudge` and `--report` ask.
+/// `smudge` holds the options the output was stripped with.
+fn strip<W: Finish>(
+    mut output: W,
+    report: bool,
+    smudge: Option<&Options>,
+    process: impl FnOnce(&mut W) -> std::result::Result<Stats, Error>,
+) -> std::result::Result<Stats, Error> {
+    let start = Instant::now();
+    let mut stats = process(&mut output)?;
+    if let Some(options) = smudge
+        && stats.bytes_out > 0
+    {
+        let newline = final_newline(options, &stats);
+        // the newline ends the output, so the line-buffered stdout writes it
+        output.write_all(newline).map_err(Error::Write)?;
+        stats.bytes_out += newline.len() as u64;
+        // it stands in for the stripped ones, rewriting them unless they match
+        let lf = newline.iter().filter(|&&b| b == b'\n').count() as u64;
+        if (stats.lf_stripped, stats.cr_stripped) != (lf, newline.len() as u64 - lf) {
+            stats.terminators_rewritten += 1;
+        }
+    }
+    output.finish().map_err(Error::Write)?;
+    if report {
+        eprint_report(&stats, start.elapsed());
+    }
+    Ok(stats)
+}
+
+/// The newline smudge ends the output with: the configured line ending, or
+/// else the kind of the newlines stripped, and `\n` if there were none.
+fn final_newline(options: &Options, stats: &Stats) -> &'static [u8] {
+    match options.line_ending() {
+        Some(line_ending) => line_ending.as_bytes(),
+        None if stats.cr_stripped > 0 && stats.lf_stripped > 0 => b"\r\n",
+        None if stats.cr_stripped > 0 => b"\r",
+        None => b"\n",
+    }
+}
+
+/// Processes stdin, or with `--pipeline` reads it on a separate thread in
+/// chunks of `pipeline` bytes.
+fn process_stdin(
+    input: impl BufRead,
+    output: &mut impl Write,
+    options: &Options,
+    pipeline: Option<usize>,
+) -> std::result::Result<Stats, Error> {
+    match pipeline {
+        Some(size) => {
+            // the lock would keep the reading thread from reading
+            drop(input);
+            let options = options.to_builder().buffer_size(size).build();
+            nln::process_pipelined(stdin(), output, &options)
+        }
+        None => nln::process(input, output, options),
+    }
+}
+
+/// Files at least this large are read on a separate thread when written to
+/// another file, so that the next read is under way while the last write
+/// completes. For smaller ones, starting the thread costs more than it saves.
+const OVERLAP_MIN_LEN: u64 = 1024 * 1024;
+
+/// Whether `input` is a regular file worth reading on a separate thread.
+fn overlaps(input: &File) -> bool {
+    input
+        .metadata()
+        .is_ok_and(|metadata| metadata.is_file() && metadata.len() >= OVERLAP_MIN_LEN)
+}
+
+/// Settings for in-place mode beyond the stripping [`Options`].
+struct InPlaceOptions {
+    /// Rewrite newlines as each file's dominant line ending.
+    eol_auto: bool,
+    smudge: bool,
+    /// Map files into memory instead of reading them.
+    mmap: bool,
+    /// Give the kernel hints about how files are read.
+    fadvise: bool,
+    /// The default options are used, so content can be copied as is.
+    plain: bool,
+    /// Print totals to stderr at the end.
+    summary: bool,
+}
+
+/// Strips every file in place, returning the exit code: 0 on success and 2 if
+/// any file could not be processed.
+fn run_in_place(
+    files: &[PathBuf],
+    options: &Options,
+    settings: &InPlaceOptions,
+    mut ring: Option<&mut Uring>,
+) -> i32 {
+    let mut totals = Summary::default();
+    let mut failed = false;
+    let mut pool = Pool::new();
+    for path in files {
+        match strip_in_place(path, options, settings, ring.as_deref_mut(), &mut pool) {
+            Ok((changed, bytes_stripped)) => totals.add(path, changed, bytes_stripped),
+            Err(err) => {
+                eprintln!("{}: {err}", path.display());
+                failed = true;
+            }
+        }
+    }
+    if settings.summary {
+        let _ = totals.write(&mut stderr(), "fixed");
+    }
+    if failed { 2 } else { 0 }
+}
+
+/// Strips a file in place, returning whether it changed and how many bytes
+/// were stripped.
+fn strip_in_place(
+    path: &Path,
+    options: &Options,
+    settings: &InPlaceOptions,
+    ring: Option<&mut Uring>,
+    pool: &mut Pool,
+) -> std::result::Result<(bool, u64), Error> {
+    // only trailing newlines go, so a file nln may write to is truncated
+    // without reading the rest of it or making a temporary file
+    if settings.plain && !settings.smudge && OpenOptions::new().write(true).open(path).is_ok() {
+        let stats = nln::process_file_in_place(path, options)?;
+        let bytes_stripped = stats.bytes_in - stats.bytes_out;
+        return Ok((bytes_stripped > 0, bytes_stripped));
+    }
+    let input = File::open(path).map_err(Error::Read)?;
+    let permissions = input.metadata().map_err(Error::Read)?.permissions();
+    let mapped = if settings.mmap {
+        mmap::map(&input)
+    } else {
+        None
+    };
+    let detected;
+    let options = if settings.eol_auto {
+        let line_ending = match &mapped {
+            Some(map) => nln::detect_eol(&map[..]),
+            None => {
+                let input = File::open(path).map_err(Error::Read)?;
+                nln::detect_eol(BufReader::with_capacity(options.buffer_size(), input))
+            }
         }
+        .map_err(Error::Read)?;
+        detected = with_line_ending(options, line_ending);
+        &detected
+    } else {
+        options
+    };
+    if settings.fadvise && mapped.is_none() {
+        fadvise::sequential(&input);
     }
+    let smudge = settings.smudge.then_some(options);
+    let mut file = AtomicFile::create(path).map_err(Error::Write)?;
+    let stats = match (&mapped, ring) {
+        _ if settings.plain => strip(&mut file, false, smudge, |file| {
+            copy::strip(&input, &mut file.file())
+        })?,
+        // a mapped file is processed as one slice, without copying it
+        (Some(map), _) => strip(
+            BufWriter::with_capacity(options.buffer_size(), &mut file),
+            false,
+            smudge,
+            |output| nln::process(&map[..], output, options),
+        )?,
+        (None, Some(ring)) => strip(&mut file, false, smudge, |file| {
+            ring.process(&input, file, options)
+        })?,
+        (None, None) if overlaps(&input) => strip(
+            BufWriter::with_capacity(options.buffer_size(), &mut file),
+            false,
+            smudge,
+            |output| nln::process_pipelined(&input, output, options),
+        )?,
+        (None, None) => strip(&mut file, false, smudge, |file| {
+            pool.strip(&input, file, options)
+        })?,
+    };
+    if settings.fadvise && mapped.is_none() {
+        fadvise::done(&input);
+    }
+    file.set_permissions(permissions).map_err(Error::Write)?;
+    file.commit().map_err(Error::Write)?;
+    // smudge and --eol may add bytes, so more can come out than went in, and
+    // their rewrites may keep the length the same
+    let bytes_stripped = stats.bytes_in.saturating_sub(stats.bytes_out);
+    let changed = stats.bytes_in != stats.bytes_out || stats.terminators_rewritten > 0;
+    Ok((changed, bytes_stripped))
+}
 
-    snickerdoodle(stdin().lock(), &mut stdout().lock())?;
-    Ok(())
+/// Rewrites newlines as `line_ending`, if the input had a dominant one.
+fn with_line_ending(options: &Options, line_ending: Option<LineEnding>) -> Options {
+    match line_ending {
+        Some(line_ending) => options.to_builder().line_ending(line_ending).build(),
+        None => options.clone(),
+    }
+}
+
+/// Settings for check mode.
+struct CheckOptions {
+    format: Format,
+    /// Stop at the first input that isn't clean.
+    fail_fast: bool,
+    /// File listing known violations to ignore.
+    baseline: Option<PathBuf>,
+    /// Record the current violations in `baseline` instead of reporting them.
+    write_baseline: bool,
+    /// File caching which inputs were clean, to skip them when unchanged.
+    cache: Option<PathBuf>,
+    /// Require inputs to end with exactly one newline instead of none.
+    ensure_final_newline: bool,
+    /// Print totals to stderr at the end.
+    summary: bool,
+    /// Map files into memory instead of reading them.
+    mmap: bool,
+    /// Give the kernel hints about how files are read.
+    fadvise: bool,
+    broken_pipe_error: bool,
+}
+
+impl CheckOptions {
+    /// Identifies the settings that decide whether an input is clean, so a
+    /// cache isn't reused across them.
+    fn cache_key(&self) -> &'static str {
+        if self.ensure_final_newline {
+            "ensure-final-newline"
+        } else {
+            "strip"
+        }
+    }
+}
+
+/// Checks every file and prints the results, returning the exit code: 0 if
+/// all files are clean, 1 if any has trailing newlines and 2 if any could not
+/// be read.
+fn run_check(files: &[PathBuf], options: &CheckOptions) -> i32 {
+    let known = match &options.baseline {
+        Some(path) if !options.write_baseline => match baseline::read(path) {
+            Ok(known) => known,
+            Err(err) => {
+                eprintln!("{}: {err}", path.display());
+                return 2;
+            }
+        },
+        _ => HashSet::new(),
+    };
+
+    let mut cache = match &options.cache {
+        Some(path) => match Cache::load(path, options.cache_key()) {
+            Ok(cache) => Some(cache),
+            Err(err) => {
+                eprintln!("{}: {err}", path.display());
+                return 2;
+            }
+        },
+        None => None,
+    };
+
+    let mut records = Vec::with_capacity(files.len());
+    let mut failed = false;
+    for path in files {
+        // stamp before reading, so a change while reading shows up next time
+        let stamp = (path != Path::new("-")).then(|| Stamp::of(path)).flatten();
+        let result = match (&cache, stamp) {
+            // an empty file has nothing to strip, so it needn't be opened
+            (_, Some(stamp)) if stamp.size == 0 => Ok(CheckRecord::clean(path, 0)),
+            (Some(cache), Some(stamp)) if cache.is_clean(path, stamp) => {
+                Ok(CheckRecord::clean(path, stamp.size))
+            }
+            _ => check_file(path, options),
+        };
+        let clean = match result {
+            Ok(mut record) => {
+                if let (Some(cache), Some(stamp)) = (&mut cache, stamp) {
+                    cache.update(path, stamp, record.is_clean());
+                }
+                record.baselined = known.contains(&record.path);
+                let clean = !record.is_violation();
+                records.push(record);
+                clean
+            }
+            Err(err) => {
+                eprintln!("{}: {err}", path.display());
+                failed = true;
+                false
+            }
+        };
+        if options.fail_fast && !clean {
+            break;
+        }
+    }
+
+    if let (Some(cache), Some(path)) = (&cache, &options.cache)
+        && let Err(err) = cache.save(path)
+    {
+        eprintln!("{}: {err}", path.display());
+        failed = true;
+    }
+
+    if let Some(path) = options
+        .baseline
+        .as_deref()
+        .filter(|_| options.write_baseline)
+    {
+        return match baseline::write(path, &records) {
+            Ok(()) if failed => 2,
+            Ok(()) => 0,
+            Err(err) => {
+                eprintln!("{}: {err}", path.display());
+                2
+            }
+        };
+    }
+
+    let mut out = stdout().lock();
+    if let Err(err) = format::write(&mut out, options.format, &records).and_then(|()| out.flush()) {
+        if err.kind() == ErrorKind::BrokenPipe && !options.broken_pipe_error {
+            return BROKEN_PIPE_STATUS;
+        }
+        eprintln!("{err}");
+        return 2;
+    }
+
+    if options.summary {
+        let mut totals = Summary::default();
+        for record in &records {
+            totals.add(
+                &record.path,
+                record.is_violation(),
+                record.bytes_stripped as u64,
+            );
+        }
+        let _ = totals.write(&mut stderr(), "violating");
+    }
+
+    if failed {
+        2
+    } else if records.iter().any(CheckRecord::is_violation) {
+        1
+    } else {
+        0
+    }
+}
+
+/// Checks a single input. With `ensure_final_newline`, the first terminator
+/// of the trailing run is allowed and its absence is a violation.
+fn check_file(path: &Path, options: &CheckOptions) -> Result<CheckRecord> {
+    let ensure_final_newline = options.ensure_final_newline;
+    let read;
+    let mapped;
+    let content: &[u8] = if path == Path::new("-") {
+        if let Some(file) = copy::stdin_file()
+            && let Some(len) = clean_len(&file, ensure_final_newline)?
+        {
+            return Ok(CheckRecord::clean(path, len));
+        }
+        let mut content = Vec::new();
+        stdin().lock().read_to_end(&mut content)?;
+        read = content;
+        &read
+    } else {
+        let mut file = File::open(path)?;
+        if let Some(len) = clean_len(&file, ensure_final_newline)? {
+            return Ok(CheckRecord::clean(path, len));
+        }
+        match options.mmap.then(|| mmap::map(&file)).flatten() {
+            Some(map) => {
+                mapped = map;
+                &mapped
+            }
+            None => {
+                if options.fadvise {
+                    fadvise::sequential(&file);
+                }
+                let mut content = Vec::new();
+                file.read_to_end(&mut content)?;
+                if options.fadvise {
+                    fadvise::done(&file);
+                }
+                read = content;
+                &read
+            }
+        }
+    };
+    let content_len = content
+        .iter()
+        .rposition(|&b| b != b'\r' && b != b'\n')
+        .map_or(0, |i| i + 1);
+    let mut trailing = &content[content_len..];
+    let mut offset = content_len;
+    if ensure_final_newline {
+        let kept = if trailing.starts_with(b"\r\n") {
+            2
+        } else {
+            trailing.len().min(1)
+        };
+        trailing = &trailing[kept..];
+        offset += kept;
+    }
+    let start = Position::START.advance(&content[..offset]);
+    Ok(CheckRecord {
+        path: path.to_path_buf(),
+        offset,
+        start,
+        end: start.advance(trailing),
+        bytes_stripped: trailing.len(),
+        missing_final_newline: ensure_final_newline && offset == content_len && offset > 0,
+        baselined: false,
+        eol_kind: eol_kind(trailing),
+    })
+}
+
+/// Checks a regular file from its current position by reading only its end,
+/// returning its length if it is clean. Violations are reported with line
+/// numbers, so a file that has any is read whole after all.
+fn clean_len(mut file: &File, ensure_final_newline: bool) -> Result<Option<u64>> {
+    if !file.metadata()?.is_file() {
+        return Ok(None);
+    }
+    let position = file.stream_position()?;
+    let tail = nln::check_seekable(file)?;
+    let clean = if !ensure_final_newline {
+        !tail.has_trailing_newlines()
+    } else {
+        match tail.trailing_bytes() {
+            0 => tail.len == position,
+            1 => true,
+            2 => {
+                let mut last = [0; 2];
+                file.seek(SeekFrom::Start(tail.trailing_start))?;
+                file.read_exact(&mut last)?;
+                file.seek(SeekFrom::Start(position))?;
+                last == *b"\r\n"
+            }
+            _ => false,
+        }
+    };
+    Ok(clean.then_some(tail.len - position))
+}
+
+/// Names the kind of line ending that makes up a run of trailing newlines.
+fn eol_kind(trailing: &[u8]) -> Option<&'static str> {
+    if trailing.is_empty() {
+        None
+    } else if trailing.iter().all(|&b| b == b'\n') {
+        Some("lf")
+    } else if trailing.iter().all(|&b| b == b'\r') {
+        Some("cr")
+    } else if trailing.len().is_multiple_of(2) && trailing.chunks(2).all(|c| c == b"\r\n") {
+        Some("crlf")
+    } else {
+        Some("mixed")
+    }
 }
 
 #[cold]
@@ -40,13 +827,113 @@ fn print_help() {
 
 USAGE:
     {} [OPTIONS]
+    {} --in-place [OPTIONS] <FILE>...
+    {} --check [OPTIONS] [FILE]...
+    {} hooks install
+    {} inspect [FILE]...
+
+COMMANDS:
+    hooks install          Install a git pre-commit hook that runs --check on
+                           staged files
+    inspect                Print the line-ending composition of each FILE
 
 OPTIONS:
-    -h, --help       Print help information
-    -v, --version    Print version information",
+    -h, --help             Print help information
+    -v, --version          Print version information
+    -o, --output <FILE>    Write to FILE instead of stdout, replacing it only once
+                           all input has been processed
+    -i, --in-place         Strip each FILE in place
+        --copy             Copy the output to the system clipboard instead of
+                           writing it to stdout. Needs the clipboard feature
+        --paste            Read the system clipboard instead of stdin. With
+                           --copy, the stripped text replaces it. Needs the
+                           clipboard feature
+        --osc52            Copy the output to the clipboard of the terminal
+                           with an OSC 52 escape sequence instead of writing
+                           it to stdout. Works over SSH, and in tmux with
+                           set-clipboard on
+        --tmux-buffer[=NAME]
+                           Load the output into the tmux paste buffer NAME,
+                           or a new buffer, instead of writing it to stdout
+        --report           Print statistics to stderr after processing
+        --keep <N>         Keep up to N trailing newlines instead of none
+        --strip-leading    Also strip newlines before the first content
+        --squeeze          Collapse runs of blank lines into one
+        --eol <lf|crlf|auto>
+                           Rewrite every newline that is kept as LF or CRLF,
+                           or as whichever of them is more common in the input
+        --buffer-size <BYTES>
+                           Size of the input and output buffers [default:
+                           65536, or 262144 for stdin and stdout when they
+                           aren't terminals]
+        --read-buffer <BYTES>
+                           Size of the stdin buffer, overriding --buffer-size
+        --write-buffer <BYTES>
+                           Size of the stdout or --output buffer, overriding
+                           --buffer-size. Output to a terminal is unbuffered
+                           by default
+        --max-pending-bytes <BYTES>
+                           Fail instead of holding back more than BYTES of
+                           newlines at once
+        --unicode          Also treat NEL, LINE SEPARATOR and PARAGRAPH
+                           SEPARATOR as newlines
+        --check            Report inputs with trailing newlines instead of
+                           stripping them; exits with 1 if any are found
+    -l, --list             Like --check, but only print the paths of inputs with
+                           trailing newlines, one per line
+    -0, --print0           With --list, end each path with NUL instead of a
+                           newline, for xargs -0
+        --summary          With --check or --in-place, print totals per file
+                           extension to stderr at the end
+        --mmap             With --check or --in-place, map regular files into
+                           memory instead of reading them. Faster for large
+                           files, but they must not change while nln runs
+        --no-fadvise       With --check or --in-place, don't tell the kernel
+                           that files are read sequentially and only once
+        --pipeline         Read stdin on a separate thread, so that reading
+                           overlaps with processing and writing. Files of at
+                           least 1 MiB are read this way when the output is a
+                           file too
+        --line-buffered    Flush the output whenever input produces some, so
+                           each line shows up as soon as it is read
+        --no-final-flush   Write out the output at the end without flushing
+                           what it is written to
+        --io-backend <auto|std|uring>
+                           How to read and write when stripping stdin or
+                           --in-place: io_uring on Linux builds with the
+                           io-uring feature, or standard blocking I/O. auto
+                           uses io_uring where the kernel supports it, and
+                           with the default options lets the kernel move
+                           content with splice(2) or copy_file_range(2)
+        --fail-fast        With --check, stop at the first input that has
+                           trailing newlines or can't be read
+        --baseline <FILE>  With --check, ignore the inputs listed in FILE
+        --write-baseline   Record the inputs that currently have trailing
+                           newlines in the --baseline FILE
+        --cache <FILE>     With --check, remember clean files in FILE and skip
+                           them while their size and mtime are unchanged
+        --ensure-final-newline
+                           With --check, require inputs to end with exactly one
+                           newline: report both missing and extra ones
+        --format <FORMAT>  Output format for --check: text, json, sarif,
+                           rdjson (reviewdog), junit or tap
+        --git-filter <clean|smudge>
+                           Run as a git filter driver: clean strips trailing
+                           newlines, smudge leaves exactly one
+        --timeout <SECONDS>
+                           Fail if no input arrives on stdin for this long
+        --timeout-flush    On timeout, finish the output as if stdin had
+                           ended instead of failing
+        --broken-pipe-error
+                           Report an error when stdout is closed early instead
+                           of exiting quietly with status 141",
         program_name,
         env!("CARGO_PKG_VERSION"),
         env!("CARGO_PKG_DESCRIPTION"),
+        program_name,
+        program_name,
+        program_name,
+        program_name,
         program_name
     );
 }
@@ -57,9 +944,28 @@ fn print_version() {
 }
 
 #[cold]
-fn eprint_unknown_argument(arg: &str) {
+fn eprint_report(stats: &Stats, elapsed: Duration) {
     eprintln!(
-        "Unknown argument: {arg}
+        "bytes read:       {}
+bytes written:    {}
+LF removed:       {}
+CR removed:       {}
+chunks read:      {}
+elapsed:          {elapsed:?}",
+        stats.bytes_in, stats.bytes_out, stats.lf_stripped, stats.cr_stripped, stats.chunks
+    );
+}
+
+#[cold]
+fn eprint_usage_error(message: &str) {
+    eprintln!(
+        "{message}
 Use --help for usage information"
     );
 }
+
+#[cold]
+fn exit_usage_error(message: &str) -> ! {
+    eprint_usage_error(message);
+    process::exit(1);
+}
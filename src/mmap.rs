@@ -0,0 +1,38 @@
+//! Memory-mapping regular files for `--mmap`.
+
+use std::fs::File;
+
+#[cfg(not(target_os = "wasi"))]
+pub use memmap2::Mmap;
+
+/// Maps `file` into memory, or returns `None` if it isn't a regular file or
+/// can't be mapped, in which case it should be read as usual.
+#[cfg(not(target_os = "wasi"))]
+pub fn map(file: &File) -> Option<Mmap> {
+    let metadata = file.metadata().ok()?;
+    // mapping an empty file fails on some platforms, and gains nothing
+    if !metadata.is_file() || metadata.len() == 0 {
+        return None;
+    }
+    // SAFETY: the map is only read. `--mmap` is documented to require that
+    // files don't change while nln runs, which is what makes this sound.
+    unsafe { Mmap::map(file) }.ok()
+}
+
+/// WASI can't map files, so they are always read.
+#[cfg(target_os = "wasi")]
+pub enum Mmap {}
+
+#[cfg(target_os = "wasi")]
+impl std::ops::Deref for Mmap {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match *self {}
+    }
+}
+
+#[cfg(target_os = "wasi")]
+pub fn map(_file: &File) -> Option<Mmap> {
+    None
+}
@@ -0,0 +1,56 @@
+//! Copying with the OSC 52 escape sequence for `--osc52`, which asks the
+//! terminal itself to set the clipboard. It works wherever the terminal is,
+//! such as at the other end of an SSH connection.
+
+use std::fs::OpenOptions;
+use std::io;
+use std::io::Write;
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Sends `content` to the clipboard of the controlling terminal.
+pub fn copy(content: &[u8]) -> io::Result<()> {
+    let mut terminal = OpenOptions::new().write(true).open(TERMINAL)?;
+    terminal.write_all(&sequence(content))?;
+    terminal.flush()
+}
+
+#[cfg(not(windows))]
+const TERMINAL: &str = "/dev/tty";
+#[cfg(windows)]
+const TERMINAL: &str = "CONOUT$";
+
+/// The escape sequence setting the clipboard to `content`, ended with BEL,
+/// which more terminals accept than ST.
+fn sequence(content: &[u8]) -> Vec<u8> {
+    let mut sequence = b"\x1b]52;c;".to_vec();
+    for chunk in content.chunks(3) {
+        let bits = chunk
+            .iter()
+            .enumerate()
+            .fold(0, |bits, (i, &b)| bits | u32::from(b) << (16 - 8 * i));
+        for i in 0..4 {
+            sequence.push(if i <= chunk.len() {
+                BASE64[(bits >> (18 - 6 * i) & 0x3f) as usize]
+            } else {
+                b'='
+            });
+        }
+    }
+    sequence.push(b'\x07');
+    sequence
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sequence() {
+        assert_eq!(sequence(b""), b"\x1b]52;c;\x07");
+        assert_eq!(sequence(b"a"), b"\x1b]52;c;YQ==\x07");
+        assert_eq!(sequence(b"ab"), b"\x1b]52;c;YWI=\x07");
+        assert_eq!(sequence(b"abc"), b"\x1b]52;c;YWJj\x07");
+        assert_eq!(sequence(b"\xff\xfe\r\nx"), b"\x1b]52;c;//4NCng=\x07");
+    }
+}
@@ -0,0 +1,60 @@
+//! Buffers reused from one file to the next by `--in-place`, so that tens of
+//! thousands of small files don't each allocate their own.
+
+use std::io::ErrorKind;
+use std::io::Read;
+use std::io::Write;
+
+use nln::Error;
+use nln::Options;
+use nln::Stats;
+use nln::Stripper;
+
+/// Memory for stripping files one after another.
+pub struct Pool {
+    stripper: Stripper,
+    input: Vec<u8>,
+    /// The output of one chunk of input, written with a single call.
+    output: Vec<u8>,
+}
+
+impl Pool {
+    pub fn new() -> Self {
+        Self {
+            stripper: Stripper::new(Options::default()),
+            input: Vec::new(),
+            output: Vec::new(),
+        }
+    }
+
+    /// Strips trailing newlines from `input` to `output` as configured by
+    /// `options`, like [`nln::process_read`].
+    pub fn strip(
+        &mut self,
+        mut input: impl Read,
+        output: &mut impl Write,
+        options: &Options,
+    ) -> Result<Stats, Error> {
+        self.stripper.reset(options.clone());
+        self.input.resize(options.buffer_size(), 0);
+        loop {
+            let n = match input.read(&mut self.input) {
+                Ok(0) => break,
+                Ok(n) => n,
+                Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+                Err(err) => return Err(Error::Read(err)),
+            };
+            self.output.clear();
+            for piece in self.stripper.push(&self.input[..n])? {
+                self.output.extend_from_slice(piece);
+            }
+            output.write_all(&self.output).map_err(Error::Write)?;
+        }
+        self.output.clear();
+        for piece in self.stripper.finish() {
+            self.output.extend_from_slice(piece);
+        }
+        output.write_all(&self.output).map_err(Error::Write)?;
+        Ok(self.stripper.stats())
+    }
+}
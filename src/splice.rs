@@ -0,0 +1,222 @@
+//! Moving content from a stdin pipe to a stdout pipe with splice(2), so it
+//! never passes through nln's memory.
+
+#[cfg(target_os = "linux")]
+pub use imp::available;
+#[cfg(target_os = "linux")]
+pub use imp::strip;
+
+#[cfg(target_os = "linux")]
+mod imp {
+    use std::fs::File;
+    use std::io;
+    use std::io::ErrorKind;
+    use std::io::Read;
+    use std::io::Write;
+    use std::os::fd::AsFd;
+    use std::os::fd::AsRawFd;
+    use std::os::unix::fs::FileTypeExt;
+    use std::ptr;
+
+    use nln::Error;
+    use nln::Stats;
+    use nln::is_newline;
+
+    /// How many bytes at the end of each chunk are read to find where its
+    /// content ends. Chunks ending in a longer run of newlines are read whole.
+    const TAIL: usize = 64;
+
+    /// Size of the buffer that chunks of newlines are read through.
+    const BUFFER: usize = 8 * 1024;
+
+    /// Whether `input` and `output` are both pipes.
+    pub fn available(input: &impl AsFd, output: &impl AsFd) -> bool {
+        is_pipe(input) && is_pipe(output)
+    }
+
+    fn is_pipe(fd: &impl AsFd) -> bool {
+        fd.as_fd()
+            .try_clone_to_owned()
+            .map(File::from)
+            .and_then(|file| file.metadata())
+            .is_ok_and(|metadata| metadata.file_type().is_fifo())
+    }
+
+    /// Strips trailing newlines from the pipe `input` to the pipe `output`
+    /// with the default options.
+    ///
+    /// Each chunk is duplicated with tee(2) to find where its content ends
+    /// without consuming it. The content is then spliced across, and only
+    /// the newlines after it are read, to be held back until more content
+    /// follows.
+    pub fn strip(input: &impl AsFd, output: &impl AsFd) -> Result<Stats, Error> {
+        // both are read and written directly, bypassing std's buffers
+        let mut input = File::from(input.as_fd().try_clone_to_owned().map_err(Error::Read)?);
+        let mut output = File::from(output.as_fd().try_clone_to_owned().map_err(Error::Write)?);
+        let (mut peek, peek_writer) = io::pipe().map_err(Error::Read)?;
+        let null = File::options()
+            .write(true)
+            .open("/dev/null")
+            .map_err(Error::Read)?;
+        // peek at as much as the input pipe holds
+        // SAFETY: F_GETPIPE_SZ only queries the pipe
+        let capacity = unsafe { libc::fcntl(input.as_raw_fd(), libc::F_GETPIPE_SZ) };
+        if capacity > 0 {
+            // SAFETY: F_SETPIPE_SZ only resizes the pipe's buffer
+            unsafe { libc::fcntl(peek_writer.as_raw_fd(), libc::F_SETPIPE_SZ, capacity) };
+        }
+
+        let mut stats = Stats::default();
+        let mut pending = Pending::default();
+        let mut buffer = [0; BUFFER];
+        loop {
+            // SAFETY: tee only takes file descriptors
+            let n = unsafe { libc::tee(input.as_raw_fd(), peek_writer.as_raw_fd(), usize::MAX, 0) };
+            let n = match n {
+                0 => break,
+                n if n > 0 => n as usize,
+                _ => match io::Error::last_os_error() {
+                    err if err.kind() == ErrorKind::Interrupted => continue,
+                    err => return Err(Error::Read(err)),
+                },
+            };
+            stats.bytes_in += n as u64;
+            stats.chunks += 1;
+
+            let mut tail = [0; TAIL];
+            let tail = &mut tail[..n.min(TAIL)];
+            splice_exact(&peek, &null, n - tail.len()).map_err(Error::Read)?;
+            peek.read_exact(tail).map_err(Error::Read)?;
+            let newlines = tail.iter().rev().take_while(|&&b| is_newline(b)).count();
+            if newlines < tail.len() {
+                stats.bytes_out += pending.write(&mut output).map_err(Error::Write)?;
+                splice_exact(&input, &output, n - newlines).map_err(Error::Write)?;
+                stats.bytes_out += (n - newlines) as u64;
+                let newlines = &mut buffer[..newlines];
+                input.read_exact(newlines).map_err(Error::Read)?;
+                pending.extend(newlines);
+            } else {
+                // the newlines may go back further than the tail, so the
+                // chunk is read through the buffer
+                let mut left = n;
+                while left > 0 {
+                    let piece = &mut buffer[..left.min(BUFFER)];
+                    input.read_exact(piece).map_err(Error::Read)?;
+                    left -= piece.len();
+                    match piece.iter().rposition(|&b| !is_newline(b)) {
+                        Some(last) => {
+                            stats.bytes_out += pending.write(&mut output).map_err(Error::Write)?;
+                            output.write_all(&piece[..=last]).map_err(Error::Write)?;
+                            stats.bytes_out += (last + 1) as u64;
+                            pending.extend(&piece[last + 1..]);
+                        }
+                        None => pending.extend(piece),
+                    }
+                }
+            }
+        }
+        for (terminator, count) in pending.runs {
+            for &b in terminator {
+                if b == b'\n' {
+                    stats.lf_stripped += count;
+                } else {
+                    stats.cr_stripped += count;
+                }
+            }
+        }
+        Ok(stats)
+    }
+
+    /// Newlines held back until content follows them, as runs of the same
+    /// terminator, so a long run takes no more memory than a short one.
+    #[derive(Default)]
+    struct Pending {
+        runs: Vec<(&'static [u8], u64)>,
+    }
+
+    impl Pending {
+        fn extend(&mut self, newlines: &[u8]) {
+            for &b in newlines {
+                // a CR followed by an LF makes a CRLF, even across chunks
+                if b == b'\n'
+                    && let Some((b"\r", count)) = self.runs.last_mut()
+                {
+                    *count -= 1;
+                    if *count == 0 {
+                        self.runs.pop();
+                    }
+                    self.push(b"\r\n");
+                } else if b == b'\n' {
+                    self.push(b"\n");
+                } else {
+                    self.push(b"\r");
+                }
+            }
+        }
+
+        fn push(&mut self, terminator: &'static [u8]) {
+            match self.runs.last_mut() {
+                Some((last, count)) if *last == terminator => *count += 1,
+                _ => self.runs.push((terminator, 1)),
+            }
+        }
+
+        /// Writes out the newlines and forgets them, returning how many bytes
+        /// they took.
+        fn write(&mut self, output: &mut impl Write) -> io::Result<u64> {
+            let mut written = 0;
+            let mut block = [0; BUFFER];
+            for (terminator, count) in self.runs.drain(..) {
+                let per_block = (BUFFER / terminator.len()) as u64;
+                for (i, b) in block.iter_mut().enumerate() {
+                    *b = terminator[i % terminator.len()];
+                }
+                let mut left = count;
+                while left > 0 {
+                    let n = left.min(per_block);
+                    output.write_all(&block[..n as usize * terminator.len()])?;
+                    left -= n;
+                }
+                written += count * terminator.len() as u64;
+            }
+            Ok(written)
+        }
+    }
+
+    /// Moves exactly `len` bytes from `from` to `to`, one of which is a pipe.
+    fn splice_exact(from: &impl AsRawFd, to: &impl AsRawFd, mut len: usize) -> io::Result<()> {
+        while len > 0 {
+            // SAFETY: splice only takes file descriptors, and null offsets
+            let n = unsafe {
+                libc::splice(
+                    from.as_raw_fd(),
+                    ptr::null_mut(),
+                    to.as_raw_fd(),
+                    ptr::null_mut(),
+                    len,
+                    libc::SPLICE_F_MOVE,
+                )
+            };
+            match n {
+                0 => return Err(ErrorKind::UnexpectedEof.into()),
+                n if n > 0 => len -= n as usize,
+                _ => match io::Error::last_os_error() {
+                    err if err.kind() == ErrorKind::Interrupted => {}
+                    err => return Err(err),
+                },
+            }
+        }
+        Ok(())
+    }
+}
+
+/// splice(2) is Linux-only, so elsewhere the input is always read.
+#[cfg(not(target_os = "linux"))]
+pub fn available<I, O>(_input: &I, _output: &O) -> bool {
+    false
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn strip<I, O>(_input: &I, _output: &O) -> Result<nln::Stats, nln::Error> {
+    Err(nln::Error::Read(std::io::ErrorKind::Unsupported.into()))
+}
@@ -0,0 +1,78 @@
+//! Totals across all files of a check or in-place run.
+
+use std::collections::BTreeMap;
+use std::io::Result;
+use std::io::Write;
+use std::path::Path;
+
+#[derive(Debug, Default, Clone, Copy)]
+struct Totals {
+    files: u64,
+    /// Files that violate the check, or that were modified in place.
+    changed: u64,
+    bytes_stripped: u64,
+}
+
+impl Totals {
+    fn add(&mut self, changed: bool, bytes_stripped: u64) {
+        self.files += 1;
+        self.changed += u64::from(changed);
+        self.bytes_stripped += bytes_stripped;
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Summary {
+    total: Totals,
+    /// Keyed by extension without the dot, or an empty string for none.
+    by_extension: BTreeMap<String, Totals>,
+}
+
+impl Summary {
+    pub fn add(&mut self, path: &Path, changed: bool, bytes_stripped: u64) {
+        let extension = path
+            .extension()
+            .map(|e| e.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        self.total.add(changed, bytes_stripped);
+        self.by_extension
+            .entry(extension)
+            .or_default()
+            .add(changed, bytes_stripped);
+    }
+
+    /// Writes the summary, describing changed files with `changed`, e.g.
+    /// "violating" or "fixed".
+    pub fn write(&self, out: &mut impl Write, changed: &str) -> Result<()> {
+        writeln!(
+            out,
+            "files scanned: {}, {changed}: {}, bytes stripped: {}",
+            self.total.files, self.total.changed, self.total.bytes_stripped
+        )?;
+        let width = self
+            .by_extension
+            .keys()
+            .map(|e| {
+                if e.is_empty() {
+                    "(none)".len()
+                } else {
+                    e.len() + 1
+                }
+            })
+            .max()
+            .unwrap_or_default();
+        for (extension, totals) in &self.by_extension {
+            let extension = if extension.is_empty() {
+                "(none)".to_string()
+            } else {
+                format!(".{extension}")
+            };
+            writeln!(
+                out,
+                "  {extension:<width$}  scanned: {}, {changed}: {}, bytes stripped: {}",
+                totals.files, totals.changed, totals.bytes_stripped
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,104 @@
+//! Reading stdin with an idle timeout.
+
+use std::io::BufRead;
+use std::io::Error;
+use std::io::ErrorKind;
+use std::io::Read;
+use std::io::Result;
+use std::sync::mpsc::Receiver;
+use std::sync::mpsc::RecvTimeoutError;
+use std::sync::mpsc::sync_channel;
+use std::thread;
+use std::time::Duration;
+
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// A reader that gives up when its source stalls for longer than a timeout.
+///
+/// Blocking reads can't be interrupted portably, so the source is read on a
+/// background thread which hands chunks over a channel. On timeout that thread
+/// is left blocked; it goes away when the process exits.
+pub struct TimeoutReader {
+    chunks: Receiver<Result<Vec<u8>>>,
+    buf: Vec<u8>,
+    pos: usize,
+    timeout: Duration,
+    /// Treat a timeout as the end of input instead of an error.
+    eof_on_timeout: bool,
+    eof: bool,
+}
+
+impl TimeoutReader {
+    /// Starts reading `inner` on a background thread.
+    ///
+    /// Fails where threads are unavailable, such as on WASI.
+    pub fn spawn(
+        mut inner: impl Read + Send + 'static,
+        timeout: Duration,
+        eof_on_timeout: bool,
+    ) -> Result<Self> {
+        let (tx, rx) = sync_channel(4);
+        thread::Builder::new().spawn(move || {
+            loop {
+                let mut buf = vec![0; CHUNK_SIZE];
+                match inner.read(&mut buf) {
+                    // dropping the sender signals EOF
+                    Ok(0) => break,
+                    Ok(n) => {
+                        buf.truncate(n);
+                        if tx.send(Ok(buf)).is_err() {
+                            break;
+                        }
+                    }
+                    Err(err) if err.kind() == ErrorKind::Interrupted => {}
+                    Err(err) => {
+                        let _ = tx.send(Err(err));
+                        break;
+                    }
+                }
+            }
+        })?;
+        Ok(Self {
+            chunks: rx,
+            buf: Vec::new(),
+            pos: 0,
+            timeout,
+            eof_on_timeout,
+            eof: false,
+        })
+    }
+}
+
+impl Read for TimeoutReader {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        let n = self.fill_buf()?.read(buf)?;
+        self.consume(n);
+        Ok(n)
+    }
+}
+
+impl BufRead for TimeoutReader {
+    fn fill_buf(&mut self) -> Result<&[u8]> {
+        if self.pos == self.buf.len() && !self.eof {
+            match self.chunks.recv_timeout(self.timeout) {
+                Ok(chunk) => {
+                    self.buf = chunk?;
+                    self.pos = 0;
+                }
+                Err(RecvTimeoutError::Disconnected) => self.eof = true,
+                Err(RecvTimeoutError::Timeout) if self.eof_on_timeout => self.eof = true,
+                Err(RecvTimeoutError::Timeout) => {
+                    return Err(Error::new(
+                        ErrorKind::TimedOut,
+                        format!("no input received within {:?}", self.timeout),
+                    ));
+                }
+            }
+        }
+        Ok(&self.buf[self.pos..])
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.pos += amt;
+    }
+}
@@ -0,0 +1,32 @@
+//! Loading the output into a tmux paste buffer for `--tmux-buffer`.
+
+use std::io::Error;
+use std::io::Result;
+use std::io::Write;
+use std::process::Command;
+use std::process::Stdio;
+
+/// Loads `content` into the paste buffer `name`, or into a new automatically
+/// named buffer, of the tmux server that `tmux` itself would use.
+pub fn load_buffer(content: &[u8], name: Option<&str>) -> Result<()> {
+    let mut command = Command::new("tmux");
+    command.arg("load-buffer");
+    if let Some(name) = name {
+        command.args(["-b", name]);
+    }
+    let mut child = command
+        .arg("-")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    let written = child.stdin.take().unwrap().write_all(content);
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        return Err(Error::other(format!(
+            "tmux load-buffer failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    written
+}
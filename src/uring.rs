@@ -0,0 +1,242 @@
+//! An io_uring backend for `--io-backend`, which reads the next chunk of input
+//! while the current one is processed and written.
+
+use std::io;
+
+pub use crate::args::IoBackend;
+
+impl IoBackend {
+    /// Sets up a ring if this backend uses one.
+    pub fn ring(self) -> io::Result<Option<Uring>> {
+        match self {
+            IoBackend::Std => Ok(None),
+            IoBackend::Auto => Ok(Uring::new().ok()),
+            IoBackend::Uring => Uring::new().map(Some),
+        }
+    }
+}
+
+#[cfg(all(target_os = "linux", feature = "io-uring"))]
+pub use imp::Uring;
+
+#[cfg(all(target_os = "linux", feature = "io-uring"))]
+mod imp {
+    use std::io;
+    use std::io::ErrorKind;
+    use std::io::IoSlice;
+    use std::mem;
+    use std::os::fd::AsFd;
+    use std::os::fd::AsRawFd;
+
+    use io_uring::IoUring;
+    use io_uring::opcode;
+    use io_uring::squeue;
+    use io_uring::types::Fd;
+    use nln::Error;
+    use nln::Options;
+    use nln::Stats;
+    use nln::Stripper;
+
+    const READ: u64 = 0;
+    const WRITE: u64 = 1;
+    /// Reads and writes at the file position, like `read(2)` and `write(2)`.
+    const FILE_POSITION: u64 = u64::MAX;
+    /// `IOV_MAX` on Linux: the most slices one vectored write takes.
+    const MAX_SLICES: usize = 1024;
+
+    /// An io_uring and the buffers it reads into.
+    pub struct Uring {
+        ring: IoUring,
+        /// One buffer is read into while the other is processed.
+        buffers: [Vec<u8>; 2],
+        in_flight: usize,
+        /// Completions that arrived while waiting for the other operation.
+        read_result: Option<i32>,
+        write_result: Option<i32>,
+    }
+
+    impl Uring {
+        /// Sets up a ring, failing if the kernel lacks io_uring or can't read
+        /// at the file position (before Linux 5.6).
+        pub fn new() -> io::Result<Self> {
+            let ring = IoUring::new(4)?;
+            if !ring.params().is_feature_rw_cur_pos() {
+                return Err(ErrorKind::Unsupported.into());
+            }
+            Ok(Self {
+                ring,
+                buffers: [Vec::new(), Vec::new()],
+                in_flight: 0,
+                read_result: None,
+                write_result: None,
+            })
+        }
+
+        /// Strips trailing newlines from `input` to `output`, like
+        /// [`nln::process`].
+        pub fn process(
+            &mut self,
+            input: &impl AsFd,
+            output: &impl AsFd,
+            options: &Options,
+        ) -> Result<Stats, Error> {
+            // an earlier run that failed may have left a read behind
+            self.drain().map_err(Error::Read)?;
+            self.read_result = None;
+            let input = Fd(input.as_fd().as_raw_fd());
+            let output = Fd(output.as_fd().as_raw_fd());
+            let size = options.buffer_size().min(u32::MAX as usize);
+            for buffer in &mut self.buffers {
+                buffer.resize(size, 0);
+            }
+            let mut stripper = Stripper::new(options.clone());
+            let mut current = 0;
+            self.start_read(input, current).map_err(Error::Read)?;
+            loop {
+                let n = self.wait(READ).map_err(Error::Read)?;
+                if n == 0 {
+                    break;
+                }
+                self.start_read(input, 1 - current).map_err(Error::Read)?;
+                // taken out so the pieces can borrow it while the ring is used
+                let buffer = mem::take(&mut self.buffers[current]);
+                let written = stripper
+                    .push(&buffer[..n])
+                    .and_then(|pieces| self.write_all(output, pieces).map_err(Error::Write));
+                self.buffers[current] = buffer;
+                written?;
+                current = 1 - current;
+            }
+            let pieces = stripper.finish();
+            self.write_all(output, pieces).map_err(Error::Write)?;
+            Ok(stripper.stats())
+        }
+
+        /// Starts reading into buffer `index`.
+        fn start_read(&mut self, input: Fd, index: usize) -> io::Result<()> {
+            let buffer = &mut self.buffers[index];
+            let read = opcode::Read::new(input, buffer.as_mut_ptr(), buffer.len() as u32)
+                .offset(FILE_POSITION)
+                .build()
+                .user_data(READ);
+            // SAFETY: the buffer is neither touched nor freed until the read
+            // has completed, which `wait` and `drop` wait for
+            unsafe { self.submit(&read) }
+        }
+
+        /// Writes all `pieces`, continuing after short writes.
+        fn write_all<'a>(
+            &mut self,
+            output: Fd,
+            pieces: impl Iterator<Item = &'a [u8]>,
+        ) -> io::Result<()> {
+            let mut slices: Vec<IoSlice<'_>> = pieces.map(IoSlice::new).collect();
+            let mut slices = &mut slices[..];
+            // drops leading empty slices, so nothing left means nothing to write
+            IoSlice::advance_slices(&mut slices, 0);
+            while !slices.is_empty() {
+                let len = slices.len().min(MAX_SLICES);
+                // `IoSlice` is ABI compatible with `iovec`
+                let write = opcode::Writev::new(output, slices.as_ptr().cast(), len as u32)
+                    .offset(FILE_POSITION)
+                    .build()
+                    .user_data(WRITE);
+                // SAFETY: the slices and the bytes they point to outlive the
+                // write, which is waited for right away
+                unsafe { self.submit(&write)? };
+                match self.wait(WRITE) {
+                    Ok(0) => return Err(ErrorKind::WriteZero.into()),
+                    Ok(n) => IoSlice::advance_slices(&mut slices, n),
+                    Err(err) if err.kind() == ErrorKind::Interrupted => {}
+                    Err(err) => return Err(err),
+                }
+            }
+            Ok(())
+        }
+
+        /// Submits `entry` to the kernel.
+        ///
+        /// ## Safety
+        ///
+        /// The memory `entry` refers to must stay valid until it completes.
+        unsafe fn submit(&mut self, entry: &squeue::Entry) -> io::Result<()> {
+            // SAFETY: upheld by the caller
+            unsafe { self.ring.submission().push(entry) }
+                .map_err(|_| io::Error::other("io_uring submission queue is full"))?;
+            self.in_flight += 1;
+            self.ring.submit()?;
+            Ok(())
+        }
+
+        /// Waits for the read or write to complete, returning how many bytes
+        /// it moved.
+        fn wait(&mut self, operation: u64) -> io::Result<usize> {
+            loop {
+                let result = if operation == READ {
+                    self.read_result.take()
+                } else {
+                    self.write_result.take()
+                };
+                if let Some(result) = result {
+                    return usize::try_from(result)
+                        .map_err(|_| io::Error::from_raw_os_error(-result));
+                }
+                self.ring.submit_and_wait(1)?;
+                self.collect();
+            }
+        }
+
+        /// Waits for every operation in flight.
+        fn drain(&mut self) -> io::Result<()> {
+            while self.in_flight > 0 {
+                self.ring.submit_and_wait(1)?;
+                self.collect();
+            }
+            Ok(())
+        }
+
+        fn collect(&mut self) {
+            for entry in self.ring.completion() {
+                self.in_flight -= 1;
+                if entry.user_data() == READ {
+                    self.read_result = Some(entry.result());
+                } else {
+                    self.write_result = Some(entry.result());
+                }
+            }
+        }
+    }
+
+    impl Drop for Uring {
+        fn drop(&mut self) {
+            if self.drain().is_err() {
+                // the kernel may still write into the buffers
+                mem::forget(mem::take(&mut self.buffers));
+            }
+        }
+    }
+}
+
+/// io_uring needs Linux and the `io-uring` feature, so standard I/O is always
+/// used without them.
+#[cfg(not(all(target_os = "linux", feature = "io-uring")))]
+pub enum Uring {}
+
+#[cfg(not(all(target_os = "linux", feature = "io-uring")))]
+impl Uring {
+    pub fn new() -> io::Result<Self> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "nln was built without io_uring support",
+        ))
+    }
+
+    pub fn process<I, O>(
+        &mut self,
+        _input: &I,
+        _output: &O,
+        _options: &nln::Options,
+    ) -> Result<nln::Stats, nln::Error> {
+        match *self {}
+    }
+}
@@ -0,0 +1,483 @@
+//! Runs the `nln` binary the way a shell would.
+
+use std::env;
+use std::fs;
+use std::io::Read;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+use std::process::Command;
+use std::process::Output;
+use std::process::Stdio;
+use std::thread;
+
+/// Runs nln with `args`, feeding it `input` on stdin.
+fn run(args: &[&str], input: &[u8]) -> Output {
+    run_in(Path::new("."), args, input)
+}
+
+/// Like [`run`], but in the directory `dir`.
+fn run_in(dir: &Path, args: &[&str], input: &[u8]) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_nln"))
+        .args(args)
+        .current_dir(dir)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut stdin = child.stdin.take().unwrap();
+    let input = input.to_vec();
+    // written on another thread, so a full stdout pipe can't deadlock it
+    let writer = thread::spawn(move || stdin.write_all(&input));
+    let output = child.wait_with_output().unwrap();
+    writer.join().unwrap().unwrap();
+    output
+}
+
+/// A directory that is removed with everything in it when dropped.
+struct TempDir(PathBuf);
+
+impl TempDir {
+    fn new(name: &str) -> Self {
+        let path = env::temp_dir().join(format!("nln-cli-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir(&path).unwrap();
+        Self(path)
+    }
+
+    fn write(&self, name: &str, content: &[u8]) -> PathBuf {
+        let path = self.0.join(name);
+        fs::write(&path, content).unwrap();
+        path
+    }
+
+    fn entries(&self) -> Vec<String> {
+        let mut entries: Vec<String> = fs::read_dir(&self.0)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect();
+        entries.sort();
+        entries
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+#[test]
+fn test_stdin_to_stdout() {
+    let output = run(&[], b"\nabc\n\ndef\r\n\n");
+    assert!(output.status.success());
+    assert_eq!(output.stdout, b"\nabc\n\ndef");
+    assert!(output.stderr.is_empty());
+
+    let output = run(
+        &["--keep", "1", "--squeeze", "--eol", "lf"],
+        b"a\r\n\r\n\r\nb\r\n\r\n",
+    );
+    assert!(output.status.success());
+    assert_eq!(output.stdout, b"a\n\nb\n");
+}
+
+#[test]
+fn test_git_filter_smudge() {
+    let cases: [(&[&str], &[u8], &[u8]); 5] = [
+        (&[], b"a\nb\n\n", b"a\nb\n"),
+        (&[], b"a\r\nb\r\n\r\n", b"a\r\nb\r\n"),
+        (&[], b"", b""),
+        (&["--eol", "crlf"], b"a\r\nb\r\n\r\n", b"a\r\nb\r\n"),
+        (&["--eol", "auto"], b"a\r\nb\r\nc", b"a\r\nb\r\nc\r\n"),
+    ];
+    for (args, input, expected) in cases {
+        let output = run(&[&["--git-filter", "smudge"], args].concat(), input);
+        assert!(output.status.success());
+        assert_eq!(output.stdout, expected, "{args:?} {input:?}");
+    }
+
+    let output = run(&["--git-filter", "smudge", "--keep", "1"], b"a\n");
+    assert_eq!(output.status.code(), Some(1));
+}
+
+#[test]
+fn test_large_input() {
+    let mut input = b"x\n".repeat(1 << 20);
+    input.extend_from_slice(b"\n\n");
+    let output = run(&[], &input);
+    assert!(output.status.success());
+    assert_eq!(output.stdout, input[..input.len() - 3]);
+}
+
+#[test]
+fn test_output_file() {
+    let dir = TempDir::new("output");
+    let output = run_in(&dir.0, &["--output", "out.txt"], b"abc\n\n");
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty());
+    assert_eq!(dir.entries(), ["out.txt"]);
+    assert_eq!(fs::read(dir.0.join("out.txt")).unwrap(), b"abc");
+}
+
+#[test]
+fn test_in_place() {
+    let dir = TempDir::new("in-place");
+    let dirty = dir.write("dirty.txt", b"a\n\nb\n\n\n");
+    let clean = dir.write("clean.txt", b"c");
+    let output = run(
+        &[
+            "--in-place",
+            dirty.to_str().unwrap(),
+            clean.to_str().unwrap(),
+        ],
+        b"",
+    );
+    assert!(output.status.success());
+    assert_eq!(fs::read(&dirty).unwrap(), b"a\n\nb");
+    assert_eq!(fs::read(&clean).unwrap(), b"c");
+    assert_eq!(dir.entries(), ["clean.txt", "dirty.txt"]);
+
+    let missing = dir.0.join("missing.txt");
+    let output = run(&["--in-place", missing.to_str().unwrap()], b"");
+    assert_eq!(output.status.code(), Some(2));
+    assert!(!output.stderr.is_empty());
+}
+
+#[test]
+fn test_in_place_summary_counts_rewrites() {
+    let dir = TempDir::new("in-place-summary");
+    let rewritten = dir.write("rewritten.txt", b"a\rb");
+    let clean = dir.write("clean.txt", b"a\nb");
+    let output = run(
+        &[
+            "--in-place",
+            "--eol",
+            "lf",
+            "--summary",
+            rewritten.to_str().unwrap(),
+            clean.to_str().unwrap(),
+        ],
+        b"",
+    );
+    assert!(output.status.success());
+    assert_eq!(fs::read(&rewritten).unwrap(), b"a\nb");
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.starts_with("files scanned: 2, fixed: 1,"),
+        "{stderr}"
+    );
+}
+
+#[test]
+fn test_check_exit_codes() {
+    let dir = TempDir::new("check");
+    dir.write("dirty.txt", b"a\n\n");
+    dir.write("clean.txt", b"b");
+
+    let output = run_in(&dir.0, &["--check", "clean.txt"], b"");
+    assert_eq!(output.status.code(), Some(0));
+    assert!(output.stdout.is_empty());
+
+    let output = run_in(&dir.0, &["--check", "dirty.txt", "clean.txt"], b"");
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(output.stdout, b"dirty.txt: 2 trailing newline bytes (lf)\n");
+
+    let output = run_in(&dir.0, &["--list", "dirty.txt", "clean.txt"], b"");
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(output.stdout, b"dirty.txt\n");
+
+    let output = run_in(&dir.0, &["--check", "missing.txt"], b"");
+    assert_eq!(output.status.code(), Some(2));
+
+    let output = run(&["--check", "-"], b"a\n");
+    assert_eq!(output.status.code(), Some(1));
+}
+
+#[test]
+fn test_usage_error() {
+    let output = run(&["--bogus"], b"");
+    assert_eq!(output.status.code(), Some(1));
+    assert!(output.stdout.is_empty());
+    assert!(
+        String::from_utf8(output.stderr)
+            .unwrap()
+            .contains("--bogus")
+    );
+}
+
+/// Starts nln on a large input and closes its stdout after the first bytes.
+fn run_broken_pipe(args: &[&str]) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_nln"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut stdin = child.stdin.take().unwrap();
+    // fails once nln has exited, which is expected
+    let writer = thread::spawn(move || {
+        let _ = stdin.write_all(&b"x\n".repeat(16 << 20));
+    });
+    let mut stdout = child.stdout.take().unwrap();
+    stdout.read_exact(&mut [0; 1]).unwrap();
+    drop(stdout);
+    let output = child.wait_with_output().unwrap();
+    writer.join().unwrap();
+    output
+}
+
+#[test]
+fn test_broken_pipe() {
+    let output = run_broken_pipe(&[]);
+    assert_eq!(output.status.code(), Some(141));
+    assert!(output.stderr.is_empty());
+
+    let output = run_broken_pipe(&["--broken-pipe-error"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(!output.stderr.is_empty());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_signals_remove_temporary_file() {
+    // the status a shell reports for a process killed by the signal
+    for (signal, status) in [("INT", 130), ("TERM", 143), ("HUP", 129)] {
+        let dir = TempDir::new(&format!("signal-{signal}"));
+        let mut child = Command::new(env!("CARGO_BIN_EXE_nln"))
+            .args(["--output", "out.txt"])
+            .current_dir(&dir.0)
+            .stdin(Stdio::piped())
+            .spawn()
+            .unwrap();
+        // stdin stays open, so nln waits with its temporary file in place
+        let mut stdin = child.stdin.take().unwrap();
+        stdin.write_all(b"abc\n").unwrap();
+        while dir.entries().is_empty() {
+            thread::sleep(std::time::Duration::from_millis(10));
+        }
+        let killed = Command::new("kill")
+            .args([&format!("-{signal}"), &child.id().to_string()])
+            .status()
+            .unwrap();
+        assert!(killed.success());
+        assert_eq!(child.wait().unwrap().code(), Some(status), "{signal}");
+        assert!(dir.entries().is_empty(), "{signal}");
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn test_output_to_fifo() {
+    use std::os::unix::fs::FileTypeExt;
+
+    let dir = TempDir::new("fifo");
+    let fifo = dir.0.join("fifo");
+    assert!(
+        Command::new("mkfifo")
+            .arg(&fifo)
+            .status()
+            .unwrap()
+            .success()
+    );
+    let reader = thread::spawn({
+        let fifo = fifo.clone();
+        move || fs::read(fifo).unwrap()
+    });
+    let output = run_in(&dir.0, &["--output", "fifo"], b"a\n\n");
+    assert!(output.status.success());
+    // checked first, as the reader waits forever on a replaced FIFO
+    assert!(fs::metadata(&fifo).unwrap().file_type().is_fifo());
+    assert_eq!(dir.entries(), ["fifo"]);
+    assert_eq!(reader.join().unwrap(), b"a");
+
+    let output = run(&["--output", "/dev/null"], b"a\n\n");
+    assert!(output.status.success());
+    assert!(
+        fs::metadata("/dev/null")
+            .unwrap()
+            .file_type()
+            .is_char_device()
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn test_in_place_through_links() {
+    let dir = TempDir::new("links");
+    let file = dir.write("file.txt", b"a\r\n\n");
+    std::os::unix::fs::symlink("file.txt", dir.0.join("symlink.txt")).unwrap();
+    fs::hard_link(&file, dir.0.join("hard.txt")).unwrap();
+    for args in [&["--in-place"][..], &["--in-place", "--eol", "lf"]] {
+        let mut args = args.to_vec();
+        args.push("symlink.txt");
+        let output = run_in(&dir.0, &args, b"");
+        assert!(output.status.success());
+        assert!(
+            fs::symlink_metadata(dir.0.join("symlink.txt"))
+                .unwrap()
+                .is_symlink()
+        );
+        assert_eq!(fs::read(&file).unwrap(), b"a");
+        assert_eq!(fs::read(dir.0.join("hard.txt")).unwrap(), b"a");
+        assert_eq!(dir.entries(), ["file.txt", "hard.txt", "symlink.txt"]);
+        fs::write(&file, b"a\r\n\n").unwrap();
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn test_hook_checks_staged_content() {
+    let dir = TempDir::new("hooks");
+    let bin = Path::new(env!("CARGO_BIN_EXE_nln")).parent().unwrap();
+    let path = env::join_paths(
+        [bin.to_path_buf()]
+            .into_iter()
+            .chain(env::split_paths(&env::var_os("PATH").unwrap())),
+    )
+    .unwrap();
+    let command = |program: &str| {
+        let mut command = Command::new(program);
+        command
+            .current_dir(&dir.0)
+            .env("PATH", &path)
+            .env("GIT_CONFIG_GLOBAL", "/dev/null")
+            .env("GIT_CONFIG_NOSYSTEM", "1");
+        command
+    };
+    let git = |args: &[&str]| assert!(command("git").args(args).status().unwrap().success());
+    let hook = || command(".git/hooks/pre-commit").output().unwrap();
+
+    git(&["init", "-q"]);
+    assert!(run_in(&dir.0, &["hooks", "install"], b"").status.success());
+
+    // a dirty version is staged, and the working tree cleaned after
+    dir.write("a.txt", b"a\n\n");
+    git(&["add", "a.txt"]);
+    dir.write("a.txt", b"a");
+    let output = hook();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("a.txt"));
+
+    // and the other way around
+    git(&["add", "a.txt"]);
+    dir.write("a.txt", b"a\n\n");
+    assert!(hook().status.success());
+}
+
+/// Output as text, with what differs between builds and machines replaced,
+/// for snapshots.
+fn snapshot_text(output: &[u8]) -> String {
+    String::from_utf8(output.to_vec())
+        .unwrap()
+        .replace(env!("CARGO_BIN_EXE_nln"), "nln")
+        .replace(env!("CARGO_PKG_VERSION"), "[version]")
+}
+
+#[test]
+fn test_snapshot_help() {
+    let output = run(&["--help"], b"");
+    assert!(output.status.success());
+    insta::assert_snapshot!("help", snapshot_text(&output.stdout));
+}
+
+#[test]
+fn test_snapshot_check_formats() {
+    let dir = TempDir::new("snapshot-formats");
+    dir.write("dirty.txt", b"a\n\n");
+    dir.write("clean.txt", b"b");
+    dir.write("crlf.txt", b"c\r\n");
+    let files = ["dirty.txt", "clean.txt", "crlf.txt"];
+    for format in ["text", "json", "sarif", "rdjson", "junit", "tap"] {
+        let mut args = vec!["--check", "--format", format];
+        args.extend(files);
+        let output = run_in(&dir.0, &args, b"");
+        assert_eq!(output.status.code(), Some(1), "{format}");
+        insta::assert_snapshot!(format!("check_{format}"), snapshot_text(&output.stdout));
+    }
+
+    let mut args = vec!["--check", "--ensure-final-newline"];
+    args.extend(files);
+    let output = run_in(&dir.0, &args, b"");
+    insta::assert_snapshot!("check_ensure_final_newline", snapshot_text(&output.stdout));
+
+    let mut args = vec!["--list"];
+    args.extend(files);
+    let output = run_in(&dir.0, &args, b"");
+    insta::assert_snapshot!("list", snapshot_text(&output.stdout));
+}
+
+#[test]
+fn test_snapshot_inspect() {
+    let dir = TempDir::new("snapshot-inspect");
+    dir.write("mixed.txt", b"a\r\nb\nc\rd\n\n");
+    let output = run_in(&dir.0, &["inspect", "mixed.txt"], b"");
+    assert!(output.status.success());
+    insta::assert_snapshot!("inspect", snapshot_text(&output.stdout));
+}
+
+#[test]
+fn test_snapshot_errors() {
+    let cases: [(&str, &[&str]); 13] = [
+        ("unknown_argument", &["--bogus"]),
+        ("invalid_keep", &["--keep", "many"]),
+        ("invalid_eol", &["--eol", "cr"]),
+        ("missing_value", &["--keep"]),
+        (
+            "conflicting_flush",
+            &["--line-buffered", "--no-final-flush"],
+        ),
+        ("copy_with_output", &["--copy", "--output", "out.txt"]),
+        ("paste_with_pipeline", &["--paste", "--pipeline"]),
+        ("osc52_with_output", &["--osc52", "-o", "out.txt"]),
+        ("tmux_buffer_with_check", &["--check", "--tmux-buffer=x"]),
+        ("format_without_check", &["--format", "json"]),
+        ("summary_without_check", &["--summary"]),
+        ("timeout_flush_without_timeout", &["--timeout-flush"]),
+        ("missing_file", &["--check", "missing.txt"]),
+    ];
+    let dir = TempDir::new("snapshot-errors");
+    for (name, args) in cases {
+        let output = run_in(&dir.0, args, b"");
+        assert!(!output.status.success(), "{name}");
+        insta::assert_snapshot!(format!("error_{name}"), snapshot_text(&output.stderr));
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn test_tmux_buffer() {
+    // a server of its own, so the user's tmux is left alone
+    let dir = TempDir::new("tmux");
+    let tmux = |args: &[&str]| {
+        Command::new("tmux")
+            .args(args)
+            .env("TMUX_TMPDIR", &dir.0)
+            .env_remove("TMUX")
+            .output()
+    };
+    let Ok(started) = tmux(&["-f", "/dev/null", "new-session", "-d"]) else {
+        // tmux isn't installed
+        return;
+    };
+    assert!(started.status.success());
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_nln"))
+        .arg("--tmux-buffer=nln")
+        .env("TMUX_TMPDIR", &dir.0)
+        .env_remove("TMUX")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(b"a\nb\n\n").unwrap();
+    let output = child.wait_with_output().unwrap();
+    let buffer = tmux(&["show-buffer", "-b", "nln"]).unwrap();
+    tmux(&["kill-server"]).unwrap();
+
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty());
+    assert_eq!(buffer.stdout, b"a\nb");
+}
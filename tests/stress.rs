@@ -0,0 +1,69 @@
+//! Feeds the `nln` binary endless newlines through pipes and checks that its
+//! memory use stays bounded, like the library's stress tests do for the
+//! engine. Pipes on both ends take the splice(2) path, which holds newlines
+//! back itself.
+//!
+//! Set `NLN_STRESS_BYTES` for longer streams than the default 64 MiB.
+
+#![cfg(target_os = "linux")]
+
+use std::env;
+use std::fs;
+use std::io::Read;
+use std::io::Write;
+use std::process::Command;
+use std::process::Stdio;
+use std::thread;
+
+/// The most resident memory nln may use, however long the stream is.
+const MAX_RSS_KIB: u64 = 32 * 1024;
+
+fn stream_len() -> usize {
+    env::var("NLN_STRESS_BYTES").map_or(64 << 20, |len| {
+        len.parse().expect("NLN_STRESS_BYTES is a number of bytes")
+    })
+}
+
+/// The peak resident memory of a running process, in KiB.
+fn peak_rss(pid: u32) -> u64 {
+    let status = fs::read_to_string(format!("/proc/{pid}/status")).unwrap();
+    let line = status
+        .lines()
+        .find_map(|line| line.strip_prefix("VmHWM:"))
+        .unwrap();
+    line.trim().trim_end_matches("kB").trim().parse().unwrap()
+}
+
+#[test]
+fn test_pipe_to_pipe() {
+    let len = stream_len();
+    for pattern in [&b"\n"[..], b"\r\n", b"\r"] {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_nln"))
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+        let mut stdout = child.stdout.take().unwrap();
+        let reader = thread::spawn(move || {
+            let mut output = Vec::new();
+            stdout.read_to_end(&mut output).unwrap();
+            output
+        });
+
+        let mut stdin = child.stdin.take().unwrap();
+        let block = pattern.repeat(4096 / pattern.len());
+        stdin.write_all(b"a").unwrap();
+        for _ in 0..len / block.len() {
+            stdin.write_all(&block).unwrap();
+        }
+        // measured while nln waits for more, holding everything back
+        let rss = peak_rss(child.id());
+        stdin.write_all(b"b").unwrap();
+        drop(stdin);
+
+        assert!(child.wait().unwrap().success());
+        let output = reader.join().unwrap();
+        assert_eq!(output.len(), 2 + len / block.len() * block.len());
+        assert!(rss < MAX_RSS_KIB, "{pattern:?}: {rss} KiB");
+    }
+}
@@ -0,0 +1,142 @@
+//! `cargo xtask gen-corpus`: seeds for the fuzz targets in `fuzz/`, so that
+//! fuzzing starts from the shapes the test data already covers.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::generate::DEFAULT_SEED;
+use crate::generate::Rng;
+use crate::generate::SizeConfig;
+use crate::generate::write_mixed_line_endings;
+use crate::generate::write_variant;
+use crate::testdata::FileVariant;
+
+/// The shapes of the test data, scaled down to seed the fuzz corpora.
+const SEED_CONFIG: SizeConfig = SizeConfig {
+    trailing_count: 50,
+    line_count: 3,
+};
+
+/// Small inputs with the shapes that matter: every file variant, mixed line
+/// endings, runs of nothing but newlines, and terminators that only some
+/// newline policies recognize.
+fn seed_inputs() -> io::Result<Vec<(String, Vec<u8>)>> {
+    let mut inputs = vec![
+        ("empty".to_string(), Vec::new()),
+        ("only_newlines".to_string(), b"\n".repeat(200)),
+        ("lone_cr".to_string(), b"a\r\r\nb\r\r\r".to_vec()),
+        ("crlf_runs".to_string(), b"\r\n\r\na\r\n\n\r\r\n".to_vec()),
+        (
+            "unicode".to_string(),
+            "a\u{85}b\u{2028}\u{2029}\r\n\u{85}".as_bytes().to_vec(),
+        ),
+    ];
+    let mut mixed = Vec::new();
+    write_mixed_line_endings(&mut mixed)?;
+    inputs.push(("mixed_line_endings".to_string(), mixed));
+    for variant in FileVariant::ALL {
+        let mut input = Vec::new();
+        write_variant(
+            &mut input,
+            &SEED_CONFIG,
+            variant,
+            &mut Rng::new(DEFAULT_SEED),
+        )?;
+        inputs.push((variant.as_str().to_string(), input));
+    }
+    Ok(inputs)
+}
+
+/// Builds fuzz inputs the way the `arbitrary` crate takes them apart, so the
+/// structured fuzz targets see the seeds as intended.
+#[derive(Default)]
+struct Unstructured(Vec<u8>);
+
+impl Unstructured {
+    fn bool(&mut self, value: bool) -> &mut Self {
+        self.0.push(u8::from(value));
+        self
+    }
+
+    fn u8(&mut self, value: u8) -> &mut Self {
+        self.0.push(value);
+        self
+    }
+
+    /// Variant `index` of an enum with `count` variants, which derived
+    /// implementations pick by scaling a `u32` to the count.
+    fn variant(&mut self, index: u32, count: u32) -> &mut Self {
+        let value = (u64::from(index) << 32).div_ceil(u64::from(count)) as u32;
+        self.0.extend_from_slice(&value.to_le_bytes());
+        self
+    }
+
+    /// A `Vec`, each element preceded by a byte saying that another follows.
+    fn bytes(&mut self, bytes: &[u8]) -> &mut Self {
+        for &byte in bytes {
+            self.0.extend_from_slice(&[1, byte]);
+        }
+        self.bool(false)
+    }
+}
+
+/// Writes seeds for every fuzz target that takes input text to
+/// `corpus/<target>/`, where `cargo fuzz run` picks them up.
+pub fn run(corpus_dir: &Path) -> io::Result<()> {
+    let mut seeds: Vec<(&str, String, Vec<u8>)> = Vec::new();
+    for (name, input) in seed_inputs()? {
+        seeds.push(("fuzz_target_1", name.clone(), input.clone()));
+        seeds.push(("differential", name.clone(), input.clone()));
+        for (i, lengths) in [&[1][..], &[2, 3], &[64]].into_iter().enumerate() {
+            let seed = Unstructured::default()
+                .bytes(&input)
+                .bytes(lengths)
+                .0
+                .clone();
+            seeds.push(("chunked", format!("{name}_{i}"), seed));
+        }
+        // write one byte, then flush and write five, over and over
+        let seed = Unstructured::default()
+            .bytes(&input)
+            .bytes(&[1, 0x85])
+            .0
+            .clone();
+        seeds.push(("writer", name.clone(), seed));
+        // the defaults; keep one, squeeze and LF; the Unicode policy
+        for (flags, keep) in [(0, 0), (0b1100, 1), (0b1111_0000, 0)] {
+            let seed = Unstructured::default()
+                .u8(flags)
+                .u8(keep)
+                .bytes(&input)
+                .0
+                .clone();
+            seeds.push(("idempotent", format!("{name}_{flags}_{keep}"), seed));
+        }
+        // the defaults; keep one with the LF policy; the CRLF policy; and
+        // squeeze, drop leading newlines and write CRLF with the Unicode policy
+        for policy in 0..4 {
+            let mut seed = Unstructured::default();
+            seed.bool(true)
+                .u8(u8::from(policy == 1))
+                .bool(policy == 3)
+                .bool(policy == 3)
+                .bool(policy == 3);
+            if policy == 3 {
+                seed.variant(1, 2);
+            }
+            seed.variant(policy, 4);
+            // the input in two chunks, split in the middle
+            let (first, second) = input.split_at(input.len() / 2);
+            seed.bool(true).bytes(first).bool(true).bytes(second);
+            seeds.push(("model", format!("{name}_{policy}"), seed.0));
+        }
+    }
+    for (target, name, seed) in &seeds {
+        let dir = corpus_dir.join(target);
+        fs::create_dir_all(&dir)?;
+        fs::write(dir.join(format!("seed_{name}")), seed)?;
+    }
+    println!("Wrote {} seeds to {}", seeds.len(), corpus_dir.display());
+    Ok(())
+}
@@ -0,0 +1,220 @@
+//! `cargo xtask gen-testdata`: the files in `testdata/` that the benchmarks
+//! and `examples/compare.rs` run on.
+
+use std::fs;
+use std::fs::File;
+use std::io;
+use std::io::BufWriter;
+use std::io::Write;
+use std::path::Path;
+use std::thread::spawn;
+
+use crate::testdata::FileVariant;
+use crate::testdata::Size;
+use crate::testdata::file_name;
+
+/// The seed used unless `--seed` is given, so that everyone benchmarks the
+/// same files.
+pub const DEFAULT_SEED: u64 = 0x6e6c6e;
+
+pub struct SizeConfig {
+    pub trailing_count: usize,
+    pub line_count: usize,
+}
+
+impl Size {
+    fn config(&self) -> SizeConfig {
+        match self {
+            Size::Small => SizeConfig {
+                trailing_count: 1000,
+                line_count: 0,
+            },
+            Size::Medium => SizeConfig {
+                trailing_count: 2000,
+                line_count: 0,
+            },
+            Size::Large => SizeConfig {
+                trailing_count: 10000,
+                line_count: 10000,
+            },
+            Size::Huge => SizeConfig {
+                trailing_count: 1000000,
+                line_count: 15000000,
+            },
+        }
+    }
+}
+
+/// xorshift64*, which is plenty for picking words and keeps the output the
+/// same on every platform and Rust version.
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        // xorshift gets stuck at 0
+        Self(seed | 1)
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+}
+
+const WORDS: [&str; 16] = [
+    "lorem",
+    "ipsum",
+    "dolor",
+    "sit",
+    "amet",
+    "consectetur",
+    "adipiscing",
+    "elit",
+    "sed",
+    "do",
+    "eiusmod",
+    "tempor",
+    "incididunt",
+    "ut",
+    "labore",
+    "magna",
+];
+
+fn generate_newlines(writer: &mut impl Write, count: usize) -> io::Result<()> {
+    for _ in 0..count {
+        writer.write_all(b"\n")?;
+    }
+    Ok(())
+}
+
+pub fn write_variant(
+    writer: &mut impl Write,
+    config: &SizeConfig,
+    variant: FileVariant,
+    rng: &mut Rng,
+) -> io::Result<()> {
+    // Lines of four to twelve words
+    for i in 1..=config.line_count {
+        write!(writer, "Line {i}:")?;
+        for _ in 0..4 + rng.below(9) {
+            write!(writer, " {}", WORDS[rng.below(WORDS.len())])?;
+        }
+        writer.write_all(b"\n")?;
+    }
+
+    // Write final content (no newline)
+    writer.write_all(b"Last line")?;
+
+    // Handle trailing newlines based on variant
+    match variant {
+        FileVariant::ManyTrailing => {
+            generate_newlines(writer, config.trailing_count)?;
+        }
+        FileVariant::ManyTrailingThenContent => {
+            generate_newlines(writer, config.trailing_count)?;
+            writer.write_all(b"Final content")?;
+        }
+        FileVariant::NoTrailing => {}
+    }
+
+    Ok(())
+}
+
+pub fn write_mixed_line_endings(writer: &mut impl Write) -> io::Result<()> {
+    writer.write_all(b"Line 1\r\n")?;
+    writer.write_all(b"Line 2\n")?;
+    writer.write_all(b"Line 3\r\n")?;
+    generate_newlines(writer, 100)?;
+    writer.write_all(b"Line 4\n")?;
+    for _ in 0..100 {
+        writer.write_all(b"\r\n")?;
+    }
+    Ok(())
+}
+
+fn generate_for_size(size: Size, testdata_dir: &Path, seed: u64) -> io::Result<()> {
+    println!("Generating {} files...", size.as_str());
+
+    let threads: Vec<_> = FileVariant::ALL
+        .into_iter()
+        .map(|variant| {
+            let path = testdata_dir.join(file_name(size, variant));
+            spawn(move || {
+                // every variant has the same lines, so they differ only at
+                // the end
+                let mut rng = Rng::new(seed);
+                let mut file = BufWriter::new(File::create(path)?);
+                write_variant(&mut file, &size.config(), variant, &mut rng)?;
+                file.flush()
+            })
+        })
+        .collect();
+    for thread in threads {
+        thread.join().unwrap()?;
+    }
+    Ok(())
+}
+
+pub fn run(testdata_dir: &Path, seed: u64) -> io::Result<()> {
+    // Create testdata directory if it doesn't exist
+    fs::create_dir_all(testdata_dir)?;
+
+    println!(
+        "Generating test data files in {} with seed {seed}...\n",
+        testdata_dir.display()
+    );
+
+    // Edge cases
+    println!("Generating edge case files...");
+
+    // File with only newlines
+    let mut file = File::create(testdata_dir.join("only_newlines.txt"))?;
+    generate_newlines(&mut file, 5000)?;
+
+    // Mixed line endings (CRLF and LF)
+    let mut file = File::create(testdata_dir.join("mixed_line_endings.txt"))?;
+    write_mixed_line_endings(&mut file)?;
+
+    // Empty file
+    File::create(testdata_dir.join("empty.txt"))?;
+
+    // Generate all size files
+    for size in Size::ALL {
+        generate_for_size(size, testdata_dir, seed)?;
+    }
+
+    println!("\nTest data files generated!");
+    println!("\nFile sizes:");
+
+    // Display file sizes
+    let entries = fs::read_dir(testdata_dir)?;
+    let mut files: Vec<_> = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().is_file())
+        .collect();
+
+    files.sort_by_key(|entry| entry.file_name());
+
+    for entry in files {
+        let metadata = entry.metadata()?;
+        let size = metadata.len();
+        let size_str = if size > 1_000_000_000 {
+            format!("{:.1}G", size as f64 / 1_000_000_000.0)
+        } else if size > 1_000_000 {
+            format!("{:.1}M", size as f64 / 1_000_000.0)
+        } else if size > 1_000 {
+            format!("{:.1}K", size as f64 / 1_000.0)
+        } else {
+            format!("{}", size)
+        };
+        println!("{:>8}  {}", size_str, entry.file_name().to_string_lossy());
+    }
+
+    Ok(())
+}
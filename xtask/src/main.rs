@@ -0,0 +1,50 @@
+//! Development tasks for nln, run with `cargo xtask <TASK>` from anywhere in
+//! the workspace.
+
+mod corpus;
+mod generate;
+mod testdata;
+
+use std::env;
+use std::path::Path;
+use std::process::ExitCode;
+
+const USAGE: &str = "\
+USAGE:
+    cargo xtask gen-testdata [--seed <N>]
+    cargo xtask gen-corpus
+
+TASKS:
+    gen-testdata    Write the files the benchmarks run on to testdata/. The
+                    same seed always gives the same files
+    gen-corpus      Write seeds for the fuzz targets to fuzz/corpus/";
+
+fn main() -> ExitCode {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .expect("xtask is in the workspace");
+    let args: Vec<String> = env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    let result = match args.as_slice() {
+        ["gen-testdata"] => generate::run(&root.join("testdata"), generate::DEFAULT_SEED),
+        ["gen-testdata", "--seed", seed] => match seed.parse() {
+            Ok(seed) => generate::run(&root.join("testdata"), seed),
+            Err(_) => {
+                eprintln!("Invalid seed: {seed}");
+                return ExitCode::FAILURE;
+            }
+        },
+        ["gen-corpus"] => corpus::run(&root.join("fuzz").join("corpus")),
+        _ => {
+            eprintln!("{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("{err}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,50 @@
+//! The test data files, shared with the benchmarks, which include this file.
+
+#[derive(Debug, Clone, Copy)]
+pub enum Size {
+    Small,
+    Medium,
+    Large,
+    Huge,
+}
+
+impl Size {
+    pub const ALL: [Size; 4] = [Size::Small, Size::Medium, Size::Large, Size::Huge];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Size::Small => "small",
+            Size::Medium => "medium",
+            Size::Large => "large",
+            Size::Huge => "huge",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum FileVariant {
+    NoTrailing,
+    ManyTrailing,
+    ManyTrailingThenContent,
+}
+
+impl FileVariant {
+    pub const ALL: [FileVariant; 3] = [
+        FileVariant::NoTrailing,
+        FileVariant::ManyTrailing,
+        FileVariant::ManyTrailingThenContent,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FileVariant::NoTrailing => "no_trailing",
+            FileVariant::ManyTrailing => "many_trailing",
+            FileVariant::ManyTrailingThenContent => "many_trailing_then_content",
+        }
+    }
+}
+
+/// The name of the file in `testdata/` with this size and variant.
+pub fn file_name(size: Size, variant: FileVariant) -> String {
+    format!("{}_{}.txt", size.as_str(), variant.as_str())
+}